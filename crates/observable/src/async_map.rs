@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+
+use futures::future::{AbortHandle, Abortable};
+
+use crate::unique_ref::UniqueRef;
+use crate::{Dispatch, ListenerSet, MapReader, Reader, Value};
+
+/// Run a future to completion in the background. On wasm this uses the browser's event loop;
+/// elsewhere an executor has to be provided with [`set_executor`] first
+#[cfg(target_arch = "wasm32")]
+pub fn spawn_local(future: impl Future<Output = ()> + 'static) {
+    wasm_bindgen_futures::spawn_local(future)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Executor = Box<dyn Fn(futures::future::LocalBoxFuture<'static, ()>)>;
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    static EXECUTOR: RefCell<Option<Executor>> = RefCell::new(None);
+}
+
+/// Provide the executor used by [`spawn_local`] on the current thread, such as the spawner of a
+/// `futures::executor::LocalPool`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_executor(spawn: impl Fn(futures::future::LocalBoxFuture<'static, ()>) + 'static) {
+    EXECUTOR.with(|executor| *executor.borrow_mut() = Some(Box::new(spawn)));
+}
+
+/// Run a future to completion in the background, on the executor provided with [`set_executor`]
+///
+/// # Panics
+///
+/// If [`set_executor`] has not been called on the current thread
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_local(future: impl Future<Output = ()> + 'static) {
+    use futures::FutureExt;
+    EXECUTOR.with(|executor| match &*executor.borrow() {
+        Some(spawn) => spawn(future.boxed_local()),
+        None => panic!("no executor has been set for this thread. See observable_rs::set_executor"),
+    })
+}
+
+/// The computation whose result we are waiting for, which is aborted once superseded or no longer wanted
+#[derive(Default)]
+struct InFlight(RefCell<Option<AbortHandle>>);
+
+impl InFlight {
+    fn replace(&self, handle: AbortHandle) {
+        if let Some(superseded) = self.0.replace(Some(handle)) {
+            superseded.abort();
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Some(handle) = self.0.get_mut().take() {
+            handle.abort();
+        }
+    }
+}
+
+impl<T: 'static> Reader<T> {
+    /// Map the value using an async function. The MapReader holds `placeholder` until the first
+    /// future resolves, and then the result of the future for the latest value. Futures which are
+    /// superseded by a newer change are canceled, so their results are never seen
+    ///
+    /// # Panics
+    ///
+    /// On native targets the futures are run with [`spawn_local`], which panics unless an executor
+    /// has been provided for the current thread with [`set_executor`]
+    pub fn map_async_with_placeholder<R, Fut>(
+        self,
+        placeholder: R,
+        f: impl Fn(&T) -> Fut + 'static,
+    ) -> MapReader<R>
+    where
+        R: 'static,
+        Fut: Future<Output = R> + 'static,
+    {
+        self.map_async_cancelable(placeholder, move |value: &T, _| f(value))
+    }
+
+    /// Like map_async_with_placeholder, but the mapper is also handed the AbortHandle of its
+    /// computation. Once superseded the future is no longer polled, and long running work which
+    /// doesn't yield can check `is_aborted` to stop early
+    pub fn map_async_cancelable<R, Fut>(
+        self,
+        placeholder: R,
+        f: impl Fn(&T, AbortHandle) -> Fut + 'static,
+    ) -> MapReader<R>
+    where
+        R: 'static,
+        Fut: Future<Output = R> + 'static,
+    {
+        let (source, source_listener_set) = self.split();
+        let value = Value::rc(placeholder);
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+        let in_flight = InFlight::default();
+
+        let closure: Rc<dyn Dispatch> = {
+            let value = Rc::downgrade(&value);
+            let listener_set = listener_set.downgrade();
+            Rc::new(move || {
+                let (handle, registration) = AbortHandle::new_pair();
+                let future = Abortable::new(f(&source.get(), handle.clone()), registration);
+                in_flight.replace(handle);
+
+                let value = value.clone();
+                let listener_set = listener_set.clone();
+                spawn_local(async move {
+                    let Ok(result) = future.await else {
+                        return;
+                    };
+                    if let (Some(value), Some(listener_set)) =
+                        (value.upgrade(), listener_set.upgrade())
+                    {
+                        value.set(result);
+                        listener_set.notify();
+                    }
+                });
+            })
+        };
+
+        closure.dispatch();
+        if let Some(source_listener_set) = source_listener_set.upgrade() {
+            source_listener_set.subscribe_weak(Rc::downgrade(&closure));
+        }
+
+        MapReader::from((value, listener_set, closure))
+    }
+}
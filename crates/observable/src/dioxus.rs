@@ -0,0 +1,91 @@
+use std::rc::Rc;
+
+use ::dioxus::prelude::*;
+
+use crate::Reader;
+
+/// Bridge a Reader into a Dioxus signal, so that the calling component re-renders whenever
+/// the observable changes. The subscription is held in the hook state, and is dropped along with the scope
+pub fn use_observable<T: Clone + 'static>(reader: Reader<T>) -> ReadOnlySignal<T> {
+    let signal = use_signal(|| reader.value_cloned());
+    use_hook(move || {
+        Rc::new(reader.subscribe(move |value: &T| {
+            let mut signal = signal;
+            signal.set(value.clone())
+        }))
+    });
+    signal.into()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use ::dioxus::{dioxus_core::NoOpMutations, prelude::*};
+
+    use super::use_observable;
+    use crate::{Observable, Reader};
+
+    /// A value which counts how often it is cloned, which is how the bridge reads it
+    struct Tracked {
+        value: u32,
+        reads: Rc<Cell<usize>>,
+    }
+
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            self.reads.set(self.reads.get() + 1);
+            Tracked {
+                value: self.value,
+                reads: self.reads.clone(),
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct Props {
+        reader: Reader<Tracked>,
+        rendered: Rc<Cell<Option<u32>>>,
+    }
+
+    fn app(props: Props) -> Element {
+        let value = use_observable(props.reader);
+        props.rendered.set(Some(value.read().value));
+        rsx! {}
+    }
+
+    #[test]
+    fn rerenders_on_change() {
+        let reads: Rc<Cell<usize>> = Rc::default();
+        let tracked = |value| Tracked {
+            value,
+            reads: reads.clone(),
+        };
+        let obs = Observable::new(tracked(1));
+        let rendered: Rc<Cell<Option<u32>>> = Rc::default();
+
+        let mut dom = VirtualDom::new_with_props(
+            app,
+            Props {
+                reader: obs.reader(),
+                rendered: rendered.clone(),
+            },
+        );
+        dom.rebuild_in_place();
+        assert_eq!(rendered.get(), Some(1));
+        assert_eq!(obs.listener_count(), 1);
+
+        obs.set(tracked(2));
+        dom.render_immediate(&mut NoOpMutations);
+        assert_eq!(rendered.get(), Some(2));
+
+        // Once the scope is gone, its subscription goes with it, so the value is neither read nor rendered
+        drop(dom);
+        assert_eq!(obs.listener_count(), 0);
+        let count = reads.get();
+        rendered.set(None);
+        obs.set(tracked(3));
+        assert_eq!(reads.get(), count);
+        assert_eq!(rendered.get(), None);
+    }
+}
@@ -0,0 +1,94 @@
+use ::leptos::prelude::*;
+use ::leptos::reactive::effect::RenderEffect;
+
+use crate::{Observable, Reader};
+
+/// Bridge a Reader into a Leptos signal which follows the observable. The subscription is owned
+/// by the current reactive owner, and is dropped along with it
+pub fn signal_from_reader<T: Clone + 'static>(reader: Reader<T>) -> Signal<T, LocalStorage> {
+    let signal = RwSignal::new_local(reader.value_cloned());
+    let sub = reader.subscribe(move |value: &T| signal.set(value.clone()));
+    StoredValue::new_local(sub);
+    signal.read_only().into()
+}
+
+/// Create an Observable which is set whenever the Leptos signal changes. The effect doing so is
+/// owned by the current reactive owner, so the Observable stops following the signal once that is disposed
+pub fn observable_from_signal<T, S>(signal: S) -> Observable<T>
+where
+    T: Clone + 'static,
+    S: Get<Value = T> + GetUntracked<Value = T> + 'static,
+{
+    let obs = Observable::new(signal.get_untracked());
+    let weak = obs.downgrade();
+    let effect = RenderEffect::new(move |prev: Option<()>| {
+        let value = signal.get();
+        // The first run just subscribes, the Observable already has this value
+        if prev.is_some() {
+            weak.set(value);
+        }
+    });
+    StoredValue::new_local(effect);
+    obs
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, future::Future, pin::Pin};
+
+    use ::leptos::{prelude::*, reactive::owner::Owner, task::Executor};
+    use futures::{
+        executor::LocalPool,
+        task::{LocalSpawnExt, SpawnExt},
+    };
+
+    use super::{observable_from_signal, signal_from_reader};
+    use crate::Observable;
+
+    thread_local! {
+        static POOL: RefCell<LocalPool> = RefCell::new(LocalPool::new());
+    }
+
+    /// Runs leptos effects on a thread local pool, which the test polls explicitly
+    struct TestExecutor;
+    impl ::leptos::task::CustomExecutor for TestExecutor {
+        fn spawn(&self, fut: Pin<Box<dyn Future<Output = ()> + Send>>) {
+            POOL.with(|pool| pool.borrow().spawner().spawn(fut).unwrap());
+        }
+        fn spawn_local(&self, fut: Pin<Box<dyn Future<Output = ()>>>) {
+            POOL.with(|pool| pool.borrow().spawner().spawn_local(fut).unwrap());
+        }
+        fn poll_local(&self) {
+            POOL.with(|pool| pool.borrow_mut().run_until_stalled());
+        }
+    }
+
+    #[test]
+    fn bidirectional_sync() {
+        Executor::init_local_custom_executor(TestExecutor).unwrap();
+        let owner = Owner::new();
+        owner.set();
+
+        // Observable -> signal
+        let obs = Observable::new(1);
+        let signal = signal_from_reader(obs.reader());
+        assert_eq!(signal.get_untracked(), 1);
+        obs.set(2);
+        assert_eq!(signal.get_untracked(), 2);
+
+        // signal -> Observable
+        let source = RwSignal::new_local(10);
+        let driven = observable_from_signal(source);
+        assert_eq!(*driven.value(), 10);
+        source.set(11);
+        Executor::poll_local();
+        assert_eq!(*driven.value(), 11);
+
+        // Disposing the owner tears down both bridges
+        owner.cleanup();
+        obs.set(3);
+        assert_eq!(signal.try_get_untracked(), None);
+        Executor::poll_local();
+        assert_eq!(*driven.value(), 11);
+    }
+}
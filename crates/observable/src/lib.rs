@@ -4,14 +4,47 @@
 //!
 //! ```
 
+/// Debug logging, which compiles away unless the `log` feature is enabled
+#[cfg(feature = "log")]
+macro_rules! debug {
+    ($($arg:tt)*) => { log::debug!(target: "observable_rs", $($arg)*) };
+}
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
 /// Public API.
+#[cfg(feature = "async")]
+mod async_map;
+#[cfg(feature = "dioxus")]
+mod dioxus;
+#[cfg(feature = "leptos")]
+mod leptos;
 mod listener_set;
 mod observable;
 mod pushable;
+mod ring_buffer;
+#[cfg(feature = "stream")]
+mod stream;
+mod sync;
 pub mod unique_ref;
 mod value;
 
 // Reexport of the public API.
+#[cfg(feature = "async")]
+#[doc(inline)]
+pub use crate::async_map::*;
+#[cfg(feature = "dioxus")]
+#[doc(inline)]
+pub use crate::dioxus::*;
+#[cfg(feature = "leptos")]
+#[doc(inline)]
+pub use crate::leptos::*;
 #[doc(inline)]
 pub use crate::listener_set::*;
 #[doc(inline)]
@@ -19,11 +52,19 @@ pub use crate::observable::*;
 #[doc(inline)]
 pub use crate::pushable::*;
 #[doc(inline)]
+pub use crate::ring_buffer::*;
+#[cfg(feature = "stream")]
+#[doc(inline)]
+pub use crate::stream::*;
+#[doc(inline)]
+pub use crate::sync::*;
+#[doc(inline)]
 pub use crate::value::*;
 
 use std::cell::Ref;
+/// What Observable and Reader have in common, for code which works with either
 pub trait Observe<T>: Sized {
-    fn value_ref(&self) -> Ref<T>;
-    fn subscribe(&self, cb: Box<dyn Fn()>) -> Subscription;
-    fn once(&self, cb: Box<dyn Fn()>) -> Subscription;
+    fn value_ref(&self) -> Ref<'_, T>;
+    fn subscribe(&self, cb: Box<dyn Fn(&T)>) -> Subscription;
+    fn once(&self, cb: Box<dyn FnOnce(&T)>) -> Subscription;
 }
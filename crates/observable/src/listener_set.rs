@@ -1,5 +1,5 @@
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     rc::{Rc, Weak},
 };
 
@@ -7,12 +7,70 @@ use std::{
 pub struct ListenerSet(RefCell<Inner>);
 
 impl ListenerSet {
+    /// Fire all listeners in the order in which they were subscribed, regardless of whether
+    /// they are once or durable listeners
     pub fn notify(&self) {
-        let working_set = self.working_set();
+        self.notify_count();
+    }
+    /// Like notify, returning the number of listeners which were invoked. Paused listeners are not counted,
+    /// and neither are listeners deferred by a batch_scope
+    ///
+    /// A notify made by one of the listeners while they are being dispatched does not recurse. All of
+    /// the current listeners are called first, and then they are all called once more, no matter how
+    /// many times they notified in the meantime. Listeners which keep on notifying are given up on after
+    /// MAX_ROUNDS of this, which is a bug in debug builds
+    ///
+    /// Mappers notified while the listeners are being dispatched are recomputed right away, so that
+    /// a subscriber reading a derived value always sees a fresh one. Subscribers notified in the
+    /// meantime wait until the dispatch is over, and are called once, seeing the final state
+    pub fn notify_count(&self) -> usize {
+        {
+            let mut inner = self.0.borrow_mut();
+            if inner.held {
+                return 0;
+            }
+            if inner.dispatching {
+                inner.deferred = true;
+                return 0;
+            }
+        }
+        let (working_set, nested) = BATCH.with(|batch| {
+            let mut batch = batch.borrow_mut();
+            let nested = batch.dispatching > 0;
+            (batch.defer(self.working_set()), nested)
+        });
+        if working_set.items.is_empty() {
+            return 0;
+        }
 
         // Now that the borrow on the listeners vec is over, we can safely call them
         // We can also be confident that we won't call any listeners which were attached during our dispatch
-        working_set.notify();
+        let _scope = Scope::enter(ScopeKind::Dispatch);
+        let _dispatching = Dispatching::enter(self);
+        let mut working_set = working_set;
+        let mut count = 0;
+        for round in 1.. {
+            count += working_set.notify();
+
+            let mut inner = self.0.borrow_mut();
+            if !std::mem::take(&mut inner.deferred) || inner.held {
+                break;
+            }
+            if round == MAX_ROUNDS {
+                debug!(
+                    "listeners still notifying after {} rounds, giving up",
+                    round
+                );
+                debug_assert!(round < MAX_ROUNDS, "listeners keep notifying each other");
+                break;
+            }
+            working_set = inner.working_set();
+            drop(inner);
+            if nested {
+                working_set = BATCH.with(|batch| batch.borrow_mut().defer(working_set));
+            }
+        }
+        count
     }
 
     pub(crate) fn working_set(&self) -> WorkingSet {
@@ -20,19 +78,77 @@ impl ListenerSet {
     }
 
     pub fn subscribe(&self, cb: impl Dispatch + 'static) -> Subscription {
-        let cb: Rc<dyn Dispatch> = Rc::new(cb);
-        self.subscribe_weak(Rc::downgrade(&cb));
-        Subscription::new(cb)
+        let mut sub = Subscription::new(Rc::new(cb));
+        self.subscribe_weak(sub.weak());
+        sub.active = Some(self.activate());
+        sub
     }
     pub fn once(&self, cb: impl FnOnce() + 'static) -> Subscription {
         let cb = RefCell::new(Some(cb));
-        let cb: Rc<dyn Dispatch> = Rc::new(move || {
+        let mut sub = Subscription::new(Rc::new(move || {
             if let Some(f) = cb.take() {
                 f();
             }
-        });
-        self.once_weak(Rc::downgrade(&cb));
-        Subscription::new(cb)
+        }));
+        self.once_weak(sub.weak());
+        sub.active = Some(self.activate());
+        sub
+    }
+    /// Call `cb` with true when the first subscription is made, and with false once the last one is
+    /// dropped, for acquiring and releasing resources only while somebody is listening. Only the
+    /// subscriptions returned by subscribe and once are counted, not listeners registered weakly
+    pub fn on_active_change(&self, cb: impl Fn(bool) + 'static) -> Subscription {
+        let activity = Rc::downgrade(&self.0.borrow().activity);
+        let sub = Subscription::new(Rc::new(move || {
+            if let Some(activity) = activity.upgrade() {
+                cb(activity.count.get() > 0)
+            }
+        }));
+        self.0
+            .borrow()
+            .activity
+            .listeners
+            .borrow_mut()
+            .push(sub.weak());
+        sub
+    }
+    /// Whether any of the subscriptions counted by on_active_change are alive
+    pub fn is_active(&self) -> bool {
+        self.0.borrow().activity.count.get() > 0
+    }
+    fn activate(&self) -> ActiveGuard {
+        let activity = self.0.borrow().activity.clone();
+        activity.count.set(activity.count.get() + 1);
+        if activity.count.get() == 1 {
+            activity.changed();
+        }
+        ActiveGuard(activity)
+    }
+    /// Register a listener for `complete`, which fires at most once
+    pub fn on_complete(&self, cb: impl FnOnce() + 'static) -> Subscription {
+        let cb = RefCell::new(Some(cb));
+        let sub = Subscription::new(Rc::new(move || {
+            if let Some(f) = cb.take() {
+                f();
+            }
+        }));
+        self.0.borrow_mut().completion.push(sub.weak());
+        sub
+    }
+    /// Signal that there will be no further notifications, firing the completion listeners
+    pub fn complete(&self) {
+        let items = std::mem::take(&mut self.0.borrow_mut().completion);
+        WorkingSet::new(items).notify();
+    }
+    /// While held, notify does nothing. Listeners can still be added, and stay registered
+    pub fn hold(&self) {
+        self.0.borrow_mut().held = true;
+    }
+    pub fn release(&self) {
+        self.0.borrow_mut().held = false;
+    }
+    pub fn is_held(&self) -> bool {
+        self.0.borrow().held
     }
     pub fn subscribe_weak(&self, cb: Weak<dyn Dispatch>) {
         self.0.borrow_mut().subscribe(Listener::Durable(cb));
@@ -43,11 +159,87 @@ impl ListenerSet {
     pub fn unsubscribe(&self, cb: Weak<dyn Dispatch>) {
         self.0.borrow_mut().unsubscribe(cb);
     }
+    /// The number of listeners which are still alive
+    pub fn len(&self) -> usize {
+        let inner = self.0.borrow();
+        inner
+            .items
+            .iter()
+            .filter(|item| match item {
+                Listener::Once(f) | Listener::Durable(f) => f.strong_count() > 0,
+            })
+            .count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Counters for tracking down listeners which are never dropped
+    pub fn metrics(&self) -> ListenerMetrics {
+        let live = self.len();
+        let inner = self.0.borrow();
+        ListenerMetrics {
+            subscribed: inner.subscribed,
+            live,
+            pruned: inner.pruned,
+        }
+    }
+}
+
+/// See ListenerSet::metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ListenerMetrics {
+    /// Every listener ever registered, including once listeners
+    pub subscribed: usize,
+    /// The listeners which are still alive
+    pub live: usize,
+    /// The listeners which were dropped, and have since been cleaned up. Dead listeners are only
+    /// cleaned up when notifying or unsubscribing
+    pub pruned: usize,
 }
 
 #[derive(Default)]
 struct Inner {
     items: Vec<Listener>,
+    completion: Vec<WorkingItem>,
+    held: bool,
+    /// Set while the listeners are being called, see notify_count
+    dispatching: bool,
+    /// Whether a listener notified again while dispatching
+    deferred: bool,
+    subscribed: usize,
+    pruned: usize,
+    activity: Rc<Activity>,
+}
+
+/// The number of live subscriptions, and who to tell when it goes from zero to one or back
+#[derive(Default)]
+struct Activity {
+    count: Cell<usize>,
+    listeners: RefCell<Vec<WorkingItem>>,
+}
+
+impl Activity {
+    fn changed(&self) {
+        let items = {
+            let mut listeners = self.listeners.borrow_mut();
+            listeners.retain(|listener| listener.strong_count() > 0);
+            listeners.clone()
+        };
+        WorkingSet::new(items).notify();
+    }
+}
+
+/// Counts a subscription as live until dropped
+struct ActiveGuard(Rc<Activity>);
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        let count = self.0.count.get() - 1;
+        self.0.count.set(count);
+        if count == 0 {
+            self.0.changed();
+        }
+    }
 }
 
 impl Inner {
@@ -55,8 +247,15 @@ impl Inner {
         // It's possible to add listeners while we are firing a listener
         // so we need to make a copy of the listeners vec so we're not mutating it while calling listener functions
         let mut working_set: Vec<WorkingItem> = Vec::new();
+        let mut pruned = 0;
 
         self.items.retain(|item| match item {
+            // A paused once listener stays registered until it is resumed and actually fires
+            Listener::Once(f) if f.upgrade().is_some_and(|f| f.is_paused()) => true,
+            Listener::Once(f) if f.strong_count() == 0 => {
+                pruned += 1;
+                false
+            }
             Listener::Once(f) => {
                 working_set.push(f.clone());
                 false
@@ -66,32 +265,154 @@ impl Inner {
                     working_set.push(f.clone());
                     true
                 }
-                None => false,
+                None => {
+                    pruned += 1;
+                    false
+                }
             },
         });
+        self.pruned += pruned;
 
         WorkingSet::new(working_set)
     }
 
     fn subscribe(&mut self, listener: Listener) {
+        self.subscribed += 1;
         self.items.push(listener);
     }
     fn unsubscribe(&mut self, cb: Weak<dyn Dispatch>) {
         let Some(cb) = cb.upgrade() else { return };
+        let mut pruned = 0;
         self.items.retain_mut(|item| {
             let f = match &item {
                 Listener::Once(f) => f,
                 Listener::Durable(f) => f,
             };
             let Some(f) = f.upgrade() else {
+                pruned += 1;
                 return false;
             };
 
-            Rc::ptr_eq(&f, &cb)
+            !Rc::ptr_eq(&f, &cb)
+        });
+        self.pruned += pruned;
+    }
+}
+
+/// How many times notify_count goes back around for listeners which notified during their own dispatch
+const MAX_ROUNDS: usize = 100;
+
+/// Marks a ListenerSet as dispatching, until dropped. Unwinding out of a listener ends it as well
+struct Dispatching<'a>(&'a ListenerSet);
+
+impl<'a> Dispatching<'a> {
+    fn enter(ls: &'a ListenerSet) -> Self {
+        ls.0.borrow_mut().dispatching = true;
+        Dispatching(ls)
+    }
+}
+
+impl Drop for Dispatching<'_> {
+    fn drop(&mut self) {
+        let mut inner = self.0 .0.borrow_mut();
+        inner.dispatching = false;
+        inner.deferred = false;
+    }
+}
+
+thread_local! {
+    static BATCH: RefCell<Batch> = RefCell::default();
+}
+
+#[derive(Default)]
+struct Batch {
+    /// The number of batch_scopes we are in
+    depth: usize,
+    /// The number of notify_counts we are in
+    dispatching: usize,
+    pending: Vec<WorkingItem>,
+}
+
+impl Batch {
+    /// Hold on to the listeners which have to wait, returning the ones to call now. In a batch_scope
+    /// they all wait until the batch ends. While dispatching only subscribers wait, and mappers are
+    /// called right away. Each listener is only fired once, no matter how many changes it was notified of
+    fn defer(&mut self, working_set: WorkingSet) -> WorkingSet {
+        if self.depth == 0 && self.dispatching == 0 {
+            return working_set;
+        }
+        let mut now = Vec::new();
+        for item in working_set.items {
+            let subscriber = item.upgrade().is_some_and(|f| f.is_subscriber());
+            if self.depth == 0 && !subscriber {
+                now.push(item);
+            } else if !self.pending.iter().any(|p| Weak::ptr_eq(p, &item)) {
+                self.pending.push(item);
+            }
+        }
+        WorkingSet::new(now)
+    }
+    /// The listeners to call now that a scope has ended, if it was the last one holding them
+    fn take_pending(&mut self) -> WorkingSet {
+        if self.depth > 0 || self.dispatching > 0 {
+            // A batch_scope inside of a dispatch may still release the mappers
+            let pending = std::mem::take(&mut self.pending);
+            return self.defer(WorkingSet::new(pending));
+        }
+        WorkingSet::new(std::mem::take(&mut self.pending))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ScopeKind {
+    Batch,
+    Dispatch,
+}
+
+/// Counts a batch_scope or a dispatch as running until dropped, then calls whatever it held back.
+/// If it is dropped because a listener panicked, the held back listeners are dropped instead
+struct Scope(ScopeKind);
+
+impl Scope {
+    fn enter(kind: ScopeKind) -> Self {
+        BATCH.with(|batch| {
+            let mut batch = batch.borrow_mut();
+            match kind {
+                ScopeKind::Batch => batch.depth += 1,
+                ScopeKind::Dispatch => batch.dispatching += 1,
+            }
+        });
+        Scope(kind)
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let pending = BATCH.with(|batch| {
+            let mut batch = batch.borrow_mut();
+            match self.0 {
+                ScopeKind::Batch => batch.depth -= 1,
+                ScopeKind::Dispatch => batch.dispatching -= 1,
+            }
+            if std::thread::panicking() {
+                if batch.depth == 0 && batch.dispatching == 0 {
+                    batch.pending.clear();
+                }
+                return WorkingSet::new(Vec::new());
+            }
+            batch.take_pending()
         });
+        pending.notify();
     }
 }
 
+/// Run `f`, deferring all notifications until it returns. Listeners then fire once, seeing the final
+/// state, rather than once per change. Nested scopes are flushed when the outermost one ends
+pub fn batch_scope<R>(f: impl FnOnce() -> R) -> R {
+    let _scope = Scope::enter(ScopeKind::Batch);
+    f()
+}
+
 // Reader needs to keep this alive. That's basically it
 enum Listener {
     Once(Weak<dyn Dispatch>),
@@ -110,30 +431,224 @@ impl WorkingSet {
 }
 
 impl WorkingSet {
-    pub(crate) fn notify(self) {
+    pub(crate) fn notify(self) -> usize {
+        let mut count = 0;
         for item in self.items {
             if let Some(f) = item.upgrade() {
+                if !f.is_paused() {
+                    count += 1;
+                }
                 f.dispatch()
             }
         }
+        count
     }
 }
 
 pub struct Subscription {
-    #[allow(dead_code)]
-    cb: Rc<dyn Dispatch>,
+    cb: Rc<Pausable>,
+    type_name: Option<&'static str>,
+    // Declared after cb, so the listener is gone by the time the last one reports inactive
+    active: Option<ActiveGuard>,
 }
 impl Subscription {
     pub fn new(cb: Rc<dyn Dispatch>) -> Self {
-        Self { cb }
+        Self {
+            cb: Rc::new(Pausable {
+                paused: Cell::new(false),
+                cb,
+            }),
+            type_name: None,
+            active: None,
+        }
+    }
+    /// Name the type of the observable subscribed to, for logging when unsubscribed
+    pub(crate) fn named(mut self, type_name: &'static str) -> Self {
+        self.type_name = Some(type_name);
+        self
+    }
+    /// The handle to register with a ListenerSet. Pausing only applies to dispatches made through it
+    pub(crate) fn weak(&self) -> Weak<dyn Dispatch> {
+        let weak: Weak<Pausable> = Rc::downgrade(&self.cb);
+        weak
+    }
+    /// Skip this subscriber on subsequent notifications, without unsubscribing it
+    pub fn pause(&self) {
+        self.cb.paused.set(true)
+    }
+    pub fn resume(&self) {
+        self.cb.paused.set(false)
+    }
+    pub fn is_paused(&self) -> bool {
+        self.cb.paused.get()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(type_name) = self.type_name {
+            debug!("unsubscribe from {}", type_name);
+        }
+    }
+}
+
+/// Holds subscriptions for as long as the bag is alive, dropping them all together
+#[derive(Default)]
+pub struct SubscriptionBag(RefCell<Vec<Subscription>>);
+
+impl SubscriptionBag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add(&self, sub: Subscription) {
+        self.0.borrow_mut().push(sub)
+    }
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+    /// Drop all of the subscriptions now, leaving the bag empty
+    pub fn clear(&self) {
+        let subs = std::mem::take(&mut *self.0.borrow_mut());
+        drop(subs);
+    }
+}
+
+/// Cancels a group of subscriptions at once, across any number of observables. Clones share the same group
+#[derive(Clone, Default)]
+pub struct CancellationToken(Rc<TokenInner>);
+
+#[derive(Default)]
+struct TokenInner {
+    cancelled: Cell<bool>,
+    subs: SubscriptionBag,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Keep the subscription until the token is canceled. A token which is already canceled drops it right away
+    pub fn hold(&self, sub: Subscription) {
+        if !self.is_cancelled() {
+            self.0.subs.add(sub)
+        }
+    }
+    /// Drop all of the subscriptions in the group, as well as any added later
+    pub fn cancel(&self) {
+        self.0.cancelled.set(true);
+        self.0.subs.clear();
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.get()
+    }
+}
+
+struct Pausable {
+    paused: Cell<bool>,
+    cb: Rc<dyn Dispatch>,
+}
+impl Dispatch for Pausable {
+    fn dispatch(&self) {
+        if !self.paused.get() {
+            self.cb.dispatch()
+        }
+    }
+    fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+    fn is_subscriber(&self) -> bool {
+        true
     }
 }
 
 pub trait Dispatch {
     fn dispatch(&self);
+    fn is_paused(&self) -> bool {
+        false
+    }
+    /// Whether this was registered by subscribe or once, rather than being a mapper keeping a
+    /// derived value up to date. See notify_count
+    fn is_subscriber(&self) -> bool {
+        false
+    }
 }
 impl<Out, F: Fn() -> Out> Dispatch for F {
     fn dispatch(&self) {
         self();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::ListenerSet;
+
+    #[test]
+    fn once_and_durable_fire_in_insertion_order() {
+        let ls = ListenerSet::default();
+        let fired: Rc<RefCell<Vec<&str>>> = Rc::default();
+
+        let record = |name: &'static str| {
+            let fired = fired.clone();
+            move || fired.borrow_mut().push(name)
+        };
+        let _subs = [
+            ls.subscribe(record("durable1")),
+            ls.once(record("once1")),
+            ls.subscribe(record("durable2")),
+            ls.once(record("once2")),
+            ls.subscribe(record("durable3")),
+        ];
+
+        ls.notify();
+        assert_eq!(
+            *fired.borrow(),
+            vec!["durable1", "once1", "durable2", "once2", "durable3"]
+        );
+
+        fired.borrow_mut().clear();
+        ls.notify();
+        assert_eq!(*fired.borrow(), vec!["durable1", "durable2", "durable3"]);
+    }
+
+    #[test]
+    fn unsubscribe_removes_only_that_listener() {
+        let ls = ListenerSet::default();
+        let fired: Rc<RefCell<Vec<&str>>> = Rc::default();
+
+        let record = |name: &'static str| {
+            let fired = fired.clone();
+            move || fired.borrow_mut().push(name)
+        };
+        let subs = [
+            ls.subscribe(record("first")),
+            ls.subscribe(record("second")),
+            ls.subscribe(record("third")),
+        ];
+
+        ls.unsubscribe(subs[1].weak());
+        ls.notify();
+        assert_eq!(*fired.borrow(), vec!["first", "third"]);
+        assert_eq!(ls.len(), 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "listeners keep notifying each other")]
+    fn endless_renotify_is_cut_off() {
+        let ls = Rc::new(ListenerSet::default());
+        let _sub = {
+            let weak = Rc::downgrade(&ls);
+            ls.subscribe(move || {
+                if let Some(ls) = weak.upgrade() {
+                    ls.notify();
+                }
+            })
+        };
+        ls.notify();
+    }
+}
@@ -1,13 +1,21 @@
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, OnceCell, Ref, RefCell};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::{Not, Range};
+use std::panic::Location;
 use std::rc::{Rc, Weak};
 
-use crate::listener_set::Subscription;
+use crate::listener_set::{CancellationToken, Subscription, SubscriptionBag};
 use crate::unique_ref::{UniqueRef, WeakRef};
-use crate::{Dispatch, ListenerSet, Pushable, Value};
+use crate::{
+    batch_scope, Dispatch, ListenerMetrics, ListenerSet, Observe, Pushable, RingBuffer,
+    TryPushable, Value,
+};
 
 pub struct Observable<T> {
     value: Rc<Value<T>>,
     listener_set: UniqueRef<ListenerSet>,
+    last_change: Cell<Option<&'static Location<'static>>>,
 }
 
 /// A reader that stores the present value - regardless of whether the writer is alive or not.
@@ -21,28 +29,155 @@ pub struct Reader<T> {
     listener_set: WeakRef<ListenerSet>,
 }
 
+/// A weak handle to an Observable, which can set it for as long as the Observable is alive
+pub struct WeakObservable<T> {
+    value: Weak<Value<T>>,
+    listener_set: WeakRef<ListenerSet>,
+}
+
 impl<T> Observable<T> {
     pub fn new(value: T) -> Self {
         Self {
             value: Value::rc(value),
             listener_set: UniqueRef::default(),
+            last_change: Cell::new(None),
         }
     }
+    /// For when the Observable itself needs to be shared, for instance by several view models
+    pub fn shared(value: T) -> Rc<Self> {
+        Rc::new(Self::new(value))
+    }
     pub fn reader(&self) -> Reader<T> {
         Reader {
             value: self.value.clone(),
             listener_set: self.listener_set.downgrade(),
         }
     }
+    pub fn downgrade(&self) -> WeakObservable<T> {
+        WeakObservable {
+            value: Rc::downgrade(&self.value),
+            listener_set: self.listener_set.downgrade(),
+        }
+    }
+}
+
+impl<T> WeakObservable<T> {
+    /// Set the value and notify, returning false if the Observable has been dropped
+    pub fn set(&self, value: T) -> bool {
+        let (Some(v), Some(ls)) = (self.value.upgrade(), self.listener_set.upgrade()) else {
+            return false;
+        };
+        v.set(value);
+        notify::<T>(&ls);
+        true
+    }
+    /// A reader for the Observable, if it is still alive
+    pub fn reader(&self) -> Option<Reader<T>> {
+        self.listener_set.upgrade()?;
+        Some(Reader {
+            value: self.value.upgrade()?,
+            listener_set: self.listener_set.clone(),
+        })
+    }
+}
+impl<T> Clone for WeakObservable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            listener_set: self.listener_set.clone(),
+        }
+    }
 }
 
 impl<T> Observable<T> {
+    #[track_caller]
     pub fn set(&self, value: T) {
+        self.record_change();
+        self.value.set(value);
+        notify::<T>(&self.listener_set);
+    }
+    /// Set the value and notify, returning the previous value
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        self.record_change();
+        let previous = self.value.replace(value);
+        notify::<T>(&self.listener_set);
+        previous
+    }
+    /// Mutate the value in place, then notify once. The value is no longer borrowed by the
+    /// time subscribers run, so they are free to read it
+    #[track_caller]
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        self.record_change();
+        self.value.update(f);
+        notify::<T>(&self.listener_set);
+    }
+    /// Mutate the value in place, notifying only if `f` returns true. For changes which subscribers
+    /// don't need to hear about, such as filling in a cache. Returns whether it notified
+    #[track_caller]
+    pub fn modify(&self, f: impl FnOnce(&mut T) -> bool) -> bool {
+        if !self.value.update(f) {
+            return false;
+        }
+        self.record_change();
+        notify::<T>(&self.listener_set);
+        true
+    }
+    /// Like set, returning the number of listeners which were invoked. Useful for spotting
+    /// observables which nobody is listening to
+    #[track_caller]
+    pub fn set_and_count(&self, value: T) -> usize {
+        self.record_change();
         self.value.set(value);
-        self.listener_set.notify();
+        notify::<T>(&self.listener_set)
+    }
+
+    /// Make several changes, notifying once when done rather than once per change. Batches nest,
+    /// only notifying when the outermost one ends. See batch_scope, which this is a shorthand for
+    pub fn batch<R>(&self, f: impl FnOnce(&Self) -> R) -> R {
+        batch_scope(|| f(self))
+    }
+
+    /// Start a transaction. Changes made while it is open don't notify, and are either committed,
+    /// notifying once, or rolled back to the value at the start. Dropping it without committing rolls back
+    pub fn transaction(&self) -> Transaction<'_, T>
+    where
+        T: Clone,
+    {
+        let was_held = self.listener_set.is_held();
+        self.listener_set.hold();
+        Transaction {
+            obs: self,
+            snapshot: Some(self.value_cloned()),
+            was_held,
+        }
+    }
+
+    /// Start hydrating. Until end_hydration, changes don't notify, and subscribers (including those which
+    /// subscribe in the meantime) wait for the hydrated value
+    pub fn begin_hydration(&self) {
+        self.listener_set.hold();
+    }
+    /// Set the hydrated value, notifying subscribers once
+    #[track_caller]
+    pub fn end_hydration(&self, value: T) {
+        self.listener_set.release();
+        self.set(value);
+    }
+    pub fn is_hydrating(&self) -> bool {
+        self.listener_set.is_held()
+    }
+
+    /// Where the most recent set or push was called from, for working out who changed the value
+    pub fn last_change_location(&self) -> Option<&'static Location<'static>> {
+        self.last_change.get()
+    }
+    #[track_caller]
+    fn record_change(&self) {
+        self.last_change.set(Some(Location::caller()));
     }
 
-    pub fn value(&self) -> Ref<T> {
+    pub fn value(&self) -> Ref<'_, T> {
         self.value.get()
     }
     pub fn value_cloned(&self) -> T
@@ -53,12 +188,70 @@ impl<T> Observable<T> {
     }
 }
 
+impl<T: PartialEq> Observable<T> {
+    /// Set the value and notify, unless it is equal to the current one. Returns whether it changed
+    #[track_caller]
+    pub fn set_if_changed(&self, value: T) -> bool {
+        if *self.value.get() == value {
+            return false;
+        }
+        self.set(value);
+        true
+    }
+}
+
+/// An open transaction on an Observable, see Observable::transaction
+pub struct Transaction<'a, T> {
+    obs: &'a Observable<T>,
+    snapshot: Option<T>,
+    /// Whether the Observable was already held, by hydration, when the transaction started
+    was_held: bool,
+}
+
+impl<'a, T> Transaction<'a, T> {
+    /// Keep the changes, notifying listeners once
+    pub fn commit(mut self) {
+        self.snapshot = None;
+        if !self.was_held {
+            self.obs.listener_set.release();
+            notify::<T>(&self.obs.listener_set);
+        }
+    }
+    /// Restore the value from the start of the transaction, without notifying
+    pub fn rollback(self) {}
+}
+
+impl<'a, T> Drop for Transaction<'a, T> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            self.obs.value.set(snapshot);
+            if !self.was_held {
+                self.obs.listener_set.release();
+            }
+        }
+    }
+}
+
 impl<T> Observable<T> {
     pub fn on_updated(&self, cb: impl Dispatch + 'static) -> Subscription {
         self.listener_set.subscribe(cb)
     }
+    /// The number of live listeners, for checking that dropped subscriptions are gone. Once listeners
+    /// which have fired are not counted
+    pub fn listener_count(&self) -> usize {
+        self.listener_set.len()
+    }
+    /// How many listeners have subscribed, are still alive, and have been cleaned up after being dropped
+    pub fn listener_metrics(&self) -> ListenerMetrics {
+        self.listener_set.metrics()
+    }
+    /// Called with true when the first subscriber arrives, and with false when the last one leaves,
+    /// see ListenerSet::on_active_change
+    pub fn on_active_change(&self, cb: impl Fn(bool) + 'static) -> Subscription {
+        self.listener_set.on_active_change(cb)
+    }
     pub fn force_notify(&self) {
-        self.listener_set.notify()
+        notify::<T>(&self.listener_set);
     }
 }
 
@@ -66,6 +259,21 @@ impl<T: 'static> Observable<T> {
     pub fn subscribe(&self, cb: impl Fn(&T) + 'static) -> Subscription {
         self.reader().subscribe(cb).unwrap()
     }
+    /// Like subscribe, but `cb` is also called right away with the current value
+    pub fn subscribe_immediate(&self, cb: impl Fn(&T) + 'static) -> Subscription {
+        self.reader().subscribe_immediate(cb).unwrap()
+    }
+    /// Subscribe, keeping the subscription in `bag` for as long as the bag is alive
+    pub fn subscribe_into(&self, bag: &SubscriptionBag, cb: impl Fn(&T) + 'static) {
+        bag.add(self.subscribe(cb))
+    }
+    /// Subscribe until `token` is canceled
+    pub fn subscribe_with_token(&self, token: &CancellationToken, cb: impl Fn(&T) + 'static) {
+        token.hold(self.subscribe(cb))
+    }
+    pub fn effect(&self, f: impl Fn(&T) + 'static) -> EffectHandle {
+        self.reader().effect(f)
+    }
     pub fn once(&self, cb: impl FnOnce(&T) + 'static) -> Subscription {
         self.reader().once(cb).unwrap()
     }
@@ -75,6 +283,29 @@ impl<T: 'static> Observable<T> {
     pub fn map_value<R: 'static>(&self, f: impl Fn(&T) -> R + 'static) -> MapReader<R> {
         self.reader().map_value(f)
     }
+    pub fn map_value_weak<R: 'static>(&self, f: impl Fn(&T) -> R + 'static) -> MapReader<R> {
+        self.reader().map_value_weak(f)
+    }
+    pub fn map_value_manual<R: 'static>(
+        &self,
+        schedule: impl Fn() + 'static,
+        f: impl Fn(&T) -> R + 'static,
+    ) -> MapReader<R> {
+        self.reader().map_value_manual(schedule, f)
+    }
+    pub fn map_value_distinct<R: PartialEq + 'static>(
+        &self,
+        f: impl Fn(&T) -> R + 'static,
+    ) -> MapReader<R> {
+        self.reader().map_value_distinct(f)
+    }
+    /// Same as map_value, with a mapper returning a Result, see Reader::try_map_value
+    pub fn try_map_value<R: 'static, E: 'static>(
+        &self,
+        f: impl Fn(&T) -> Result<R, E> + 'static,
+    ) -> MapReader<Result<R, E>> {
+        self.reader().try_map_value(f)
+    }
 
     pub fn map_reader<R: Clone + 'static>(
         &self,
@@ -88,394 +319,2353 @@ impl<T, V> Observable<V>
 where
     V: Pushable<Value = T>,
 {
+    /// Push an item, notifying only if the collection changed. Pushing a duplicate into a set does not notify
+    #[track_caller]
     pub fn push(&self, item: T) {
-        self.value.push(item);
-        self.listener_set.notify();
+        if self.value.push(item) {
+            self.record_change();
+            notify::<V>(&self.listener_set);
+        }
+    }
+    /// Push all of the items, then notify once, if the collection changed. Much cheaper than
+    /// pushing them one at a time when loading a batch of rows
+    #[track_caller]
+    pub fn extend(&self, items: impl IntoIterator<Item = T>) {
+        if self.value.extend(items) {
+            self.record_change();
+            notify::<V>(&self.listener_set);
+        }
     }
 }
 
-impl<T> Default for Observable<T>
+impl<T, V> Observable<V>
 where
-    T: Default,
+    V: TryPushable<Value = T>,
 {
-    fn default() -> Self {
-        Observable::new(T::default())
+    /// Push an item, notifying only if the collection accepted it. A rejected item is handed back
+    #[track_caller]
+    pub fn try_push(&self, item: T) -> Result<(), T> {
+        self.value.try_push(item)?;
+        self.record_change();
+        notify::<V>(&self.listener_set);
+        Ok(())
     }
 }
 
-impl<T: 'static> Reader<T> {
-    pub fn map_value<R: 'static>(self, f: impl Fn(&T) -> R + 'static) -> MapReader<R> {
-        use crate::map_obs;
-        let r = self;
-        map_obs!(f, r)
+impl<T: 'static> Observable<RingBuffer<T>> {
+    /// Subscribe to the items pushed into this history buffer. The callback is first called right
+    /// away with up to the `n` most recent items, oldest first, and then with every item pushed since
+    /// it was last called. Notifications which pushed nothing new don't call it. Items which were
+    /// evicted before it could be called with them are skipped
+    pub fn subscribe_with_replay(&self, n: usize, cb: impl Fn(&T) + 'static) -> Subscription {
+        let delivered = {
+            let history = self.value();
+            for item in history.iter().skip(history.len().saturating_sub(n)) {
+                cb(item)
+            }
+            Cell::new(history.total_pushed())
+        };
+        self.subscribe(move |history: &RingBuffer<T>| {
+            let total = history.total_pushed();
+            // A smaller total means the buffer was replaced, in which case all of its items are new
+            let new = if total < delivered.get() {
+                total
+            } else {
+                total - delivered.get()
+            };
+            delivered.set(total);
+            let new = new.min(history.len());
+            for item in history.iter().skip(history.len() - new) {
+                cb(item)
+            }
+        })
     }
+}
 
-    pub fn map_reader<R: Clone + 'static>(
-        self,
-        f: impl Fn(&T) -> Reader<R> + 'static,
-    ) -> MapReader<R> {
-        MapReader::new_dyn(move |ctx| {
-            let t = ctx.track(&self);
-            let r_reader = f(&t);
-            let r_val = ctx.track_dyn(&r_reader);
-            r_val.clone()
+impl<T: Clone + 'static> Observable<Vec<T>> {
+    /// A view of the items sorted by `key`, which is re-sorted on every change. The sort is stable
+    pub fn sorted_by<K: Ord>(&self, key: impl Fn(&T) -> K + 'static) -> MapReader<Vec<T>> {
+        self.map_value(move |items: &Vec<T>| {
+            let mut sorted = items.clone();
+            sorted.sort_by_key(|item| key(item));
+            sorted
         })
     }
-
-    pub fn reader(self) -> Self {
-        self
+    /// The items by `key`, rebuilt on every change. When several items share a key, the last one wins
+    pub fn indexed_by<K: Eq + Hash + 'static>(
+        &self,
+        key: impl Fn(&T) -> K + 'static,
+    ) -> MapReader<HashMap<K, T>> {
+        self.map_value(move |items: &Vec<T>| {
+            items.iter().map(|item| (key(item), item.clone())).collect()
+        })
     }
 }
 
-impl<T> Reader<T> {
-    pub fn value(&self) -> Ref<T> {
-        self.value.get()
+impl<T: 'static> Observable<Vec<T>> {
+    /// The number of items, which only notifies when the length changes
+    pub fn len_reader(&self) -> MapReader<usize> {
+        let (source, source_listener_set) = self.reader().split();
+        let value = Value::rc(source.get().len());
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+
+        let closure: Rc<dyn Dispatch> = {
+            let value = Rc::downgrade(&value);
+            let listener_set = listener_set.downgrade();
+            Rc::new(move || {
+                let (Some(value), Some(listener_set)) = (value.upgrade(), listener_set.upgrade())
+                else {
+                    return;
+                };
+                let len = source.get().len();
+                if *value.get() != len {
+                    value.set(len);
+                    listener_set.notify();
+                }
+            })
+        };
+        if let Some(ls) = source_listener_set.upgrade() {
+            ls.subscribe_weak(Rc::downgrade(&closure));
+        }
+
+        MapReader::from((value, listener_set, closure))
     }
-    pub fn value_cloned(&self) -> T
-    where
-        T: Clone,
-    {
-        self.value.get().clone()
+    /// Modify the item at `index` in place and notify, returning false if there is no such item
+    #[track_caller]
+    pub fn modify_at(&self, index: usize, f: impl FnOnce(&mut T)) -> bool {
+        let found = self
+            .value
+            .update(|items| items.get_mut(index).map(f).is_some());
+        if found {
+            self.record_change();
+            notify::<Vec<T>>(&self.listener_set);
+        }
+        found
     }
-    pub fn split(self) -> (Rc<Value<T>>, WeakRef<ListenerSet>) {
-        (self.value, self.listener_set)
+}
+
+impl<T: Clone + 'static> Observable<Vec<Observable<T>>> {
+    /// The current values of the inner observables, which updates when the list changes or when
+    /// any of the observables in it does. Observables are subscribed to as they are added to the
+    /// list, and unsubscribed from as they are removed
+    pub fn flatten(&self) -> MapReader<Vec<T>> {
+        let outer = self.reader();
+        MapReader::new_dyn(move |ctx| {
+            let items = ctx.track(&outer);
+            items
+                .iter()
+                .map(|inner| {
+                    let inner = inner.reader();
+                    let value = ctx.track_dyn(&inner).clone();
+                    value
+                })
+                .collect()
+        })
     }
 }
-impl<T: 'static> Reader<T> {
-    pub fn subscribe(&self, cb: impl Fn(&T) + 'static) -> Option<Subscription> {
-        let value = Rc::downgrade(&self.value);
-        let sub = self.listener_set.upgrade()?.subscribe(move || {
-            if let Some(value) = value.upgrade() {
-                cb(&value.get())
-            }
-        });
-        Some(sub)
+
+/// An item which moved from one index to another, the items in between shifting over by one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveEvent {
+    pub from: usize,
+    pub to: usize,
+}
+
+impl MoveEvent {
+    /// The single move which turns `old` into `new`, if that's how they differ
+    pub fn between<T: PartialEq>(old: &[T], new: &[T]) -> Option<Self> {
+        if old.len() != new.len() {
+            return None;
+        }
+        let start = old.iter().zip(new).position(|(a, b)| a != b)?;
+        let end = old.len()
+            - 1
+            - old
+                .iter()
+                .rev()
+                .zip(new.iter().rev())
+                .position(|(a, b)| a != b)?;
+
+        if new[end] == old[start] && new[start..end] == old[start + 1..=end] {
+            Some(MoveEvent {
+                from: start,
+                to: end,
+            })
+        } else if new[start] == old[end] && new[start + 1..=end] == old[start..end] {
+            Some(MoveEvent {
+                from: end,
+                to: start,
+            })
+        } else {
+            None
+        }
     }
-    pub fn once(&self, cb: impl FnOnce(&T) + 'static) -> Option<Subscription> {
-        let value = Rc::downgrade(&self.value);
-        let sub = self.listener_set.upgrade()?.once(move || {
-            if let Some(value) = value.upgrade() {
-                cb(&value.get())
+}
+
+impl<T: 'static> Observable<Vec<T>> {
+    /// Move the item at `from` to `to`, shifting the ones in between, and notify. Does nothing if
+    /// either index is out of bounds
+    #[track_caller]
+    pub fn move_item(&self, from: usize, to: usize) {
+        let moved = self.value.update(|items| {
+            if from >= items.len() || to >= items.len() {
+                return false;
             }
+            let item = items.remove(from);
+            items.insert(to, item);
+            true
         });
-        Some(sub)
+        if moved {
+            self.record_change();
+            notify::<Vec<T>>(&self.listener_set);
+        }
     }
 }
-impl<T> Reader<T> {
-    pub fn on_updated(&self, cb: impl Fn() + 'static) -> Option<Subscription> {
-        let sub = self.listener_set.upgrade()?.subscribe(cb);
-        Some(sub)
+
+impl<T: Clone + PartialEq + 'static> Observable<Vec<T>> {
+    /// Subscribe to the changes which moved a single item, as made by move_item. Other changes are ignored
+    pub fn subscribe_moves(&self, cb: impl Fn(&MoveEvent) + 'static) -> Subscription {
+        let prev = RefCell::new(self.value_cloned());
+        self.subscribe(move |current: &Vec<T>| {
+            let event = MoveEvent::between(&prev.borrow(), current);
+            prev.replace(current.clone());
+            if let Some(event) = event {
+                cb(&event)
+            }
+        })
     }
-    pub fn force_notify(&self) {
-        if let Some(ls) = self.listener_set.upgrade() {
-            ls.notify()
-        }
+    /// Subscribe to the elements which were added to and removed from the vec by each change.
+    /// Elements are compared as a multiset, so reordering alone is not reported, and the
+    /// callback is skipped when nothing was added or removed
+    pub fn diff_subscribe(&self, cb: impl Fn(&[T], &[T]) + 'static) -> Subscription {
+        let prev = RefCell::new(self.value_cloned());
+        self.subscribe(move |current: &Vec<T>| {
+            let mut added = current.clone();
+            let mut removed = Vec::new();
+            for item in prev.borrow().iter() {
+                match added.iter().position(|a| a == item) {
+                    Some(index) => {
+                        added.remove(index);
+                    }
+                    None => removed.push(item.clone()),
+                }
+            }
+            prev.replace(current.clone());
+
+            if !added.is_empty() || !removed.is_empty() {
+                cb(&added, &removed)
+            }
+        })
     }
 }
-impl<T> Clone for Reader<T> {
-    fn clone(&self) -> Self {
-        Self {
-            value: self.value.clone(),
-            listener_set: self.listener_set.clone(),
+
+/// A change to a string, as the replacement of one range of the old string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextDiff {
+    /// The byte range of the old string which was replaced. Empty for a pure insertion
+    pub range: Range<usize>,
+    /// What it was replaced with. Empty for a pure deletion
+    pub inserted: String,
+}
+
+impl TextDiff {
+    /// The smallest single replacement turning `old` into `new`, found by trimming their common
+    /// prefix and suffix. None if they are equal
+    pub fn between(old: &str, new: &str) -> Option<Self> {
+        if old == new {
+            return None;
         }
+        let prefix: usize = old
+            .chars()
+            .zip(new.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        let (old_rest, new_rest) = (&old[prefix..], &new[prefix..]);
+        let suffix: usize = old_rest
+            .chars()
+            .rev()
+            .zip(new_rest.chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        Some(TextDiff {
+            range: prefix..old.len() - suffix,
+            inserted: new[prefix..new.len() - suffix].to_string(),
+        })
     }
 }
 
-/// MapReader is a handle to the latest output value of a map function.
-/// It is essential the the MapReader (or its clones) be the only struct to have a strong reference
-/// to said closure. This is because we want the closure to be able to close over owned/strong references
-/// to its upstream Readers WITHOUT creating a cycle.
-///
-/// The weak ref must be between the Observable and the map function. NOT inside the map function
-pub struct MapReader<T> {
-    value: Rc<Value<T>>,
-    listener_set: UniqueRef<ListenerSet>,
-    #[allow(dead_code, clippy::type_complexity)]
-    closure: Rc<dyn Dispatch>,
+impl Observable<String> {
+    /// Subscribe to the changes of the string as minimal diffs against its previous value, rather
+    /// than the whole string. The callback is skipped when the string did not actually change
+    pub fn diff_subscribe(&self, cb: impl Fn(&TextDiff) + 'static) -> Subscription {
+        let prev = RefCell::new(self.value_cloned());
+        self.subscribe(move |current: &String| {
+            let diff = TextDiff::between(&prev.borrow(), current);
+            if let Some(diff) = diff {
+                prev.replace(current.clone());
+                cb(&diff)
+            }
+        })
+    }
 }
 
-impl<T> From<(Rc<Value<T>>, UniqueRef<ListenerSet>, Rc<dyn Dispatch>)> for MapReader<T> {
-    fn from(
-        (value, listener_set, closure): (Rc<Value<T>>, UniqueRef<ListenerSet>, Rc<dyn Dispatch>),
-    ) -> Self {
-        MapReader {
-            value,
-            listener_set,
-            closure,
-        }
+impl<K, V> Observable<HashMap<K, V>>
+where
+    K: Clone + Eq + Hash + 'static,
+    V: Clone + PartialEq + 'static,
+{
+    /// Map each entry of the map. Results are memoized per key in an LRU cache holding up to
+    /// `capacity` entries, so that a change only reruns `f` for the entries which were added, whose
+    /// value changed, or which were evicted. Each cached entry keeps a clone of its value to compare
+    /// against. Entries for removed keys are evicted right away
+    pub fn map_entries<R: Clone + 'static>(
+        &self,
+        capacity: usize,
+        f: impl Fn(&K, &V) -> R + 'static,
+    ) -> MapReader<HashMap<K, R>> {
+        let memo: RefCell<HashMap<K, (V, R, u64)>> = RefCell::default();
+        let tick = Cell::new(0u64);
+        self.map_value(move |map: &HashMap<K, V>| {
+            let mut memo = memo.borrow_mut();
+            memo.retain(|k, _| map.contains_key(k));
+            let out = map
+                .iter()
+                .map(|(k, v)| {
+                    tick.set(tick.get() + 1);
+                    let entry = match memo.get_mut(k) {
+                        Some((prev, r, used)) if prev == v => {
+                            *used = tick.get();
+                            r.clone()
+                        }
+                        _ => {
+                            let r = f(k, v);
+                            memo.insert(k.clone(), (v.clone(), r.clone(), tick.get()));
+                            r
+                        }
+                    };
+                    (k.clone(), entry)
+                })
+                .collect();
+            if memo.len() > capacity {
+                let mut by_use: Vec<(u64, K)> = memo
+                    .iter()
+                    .map(|(k, (_, _, used))| (*used, k.clone()))
+                    .collect();
+                by_use.sort_unstable_by_key(|(used, _)| *used);
+                for (_, k) in by_use.into_iter().take(memo.len() - capacity) {
+                    memo.remove(&k);
+                }
+            }
+            out
+        })
     }
 }
-impl<T> MapReader<T> {
-    pub fn value(&self) -> Ref<T> {
-        self.value.get()
+
+impl Observable<f64> {
+    /// Subscribe, only firing once the value has moved by at least `min_delta` since the
+    /// last time the callback fired (or since subscribing, if it has not yet fired)
+    pub fn subscribe_on_delta(&self, min_delta: f64, cb: impl Fn(&f64) + 'static) -> Subscription {
+        let last = Cell::new(*self.value());
+        self.subscribe(move |value: &f64| {
+            if (*value - last.get()).abs() >= min_delta {
+                last.set(*value);
+                cb(value)
+            }
+        })
     }
-    pub fn value_cloned(&self) -> T
-    where
-        T: Clone,
-    {
-        self.value.get().clone()
+    pub fn clamp(&self, min: f64, max: f64) -> MapReader<f64> {
+        self.reader().clamp(min, max)
     }
-
-    pub fn reader(&self) -> Reader<T> {
-        Reader {
-            value: self.value.clone(),
-            listener_set: self.listener_set.downgrade(),
-        }
+    pub fn min_with(&self, other: Reader<f64>) -> MapReader<f64> {
+        self.reader().min_with(other)
     }
-    pub fn listener_set(&self) -> WeakRef<ListenerSet> {
-        self.listener_set.downgrade()
+    pub fn max_with(&self, other: Reader<f64>) -> MapReader<f64> {
+        self.reader().max_with(other)
     }
 }
-impl<T: 'static> MapReader<T> {
-    pub fn subscribe(&self, cb: impl Fn(&T) + 'static) -> Subscription {
-        self.reader().subscribe(cb).unwrap()
+
+impl Observable<bool> {
+    /// Flip the value and notify
+    #[track_caller]
+    pub fn toggle(&self) {
+        self.update(|value| *value = !*value)
     }
-    pub fn once(&self, cb: impl FnOnce(&T) + 'static) -> Subscription {
-        self.reader().once(cb).unwrap()
+    pub fn and(&self, other: Reader<bool>) -> MapReader<bool> {
+        self.reader().and(other)
+    }
+    pub fn or(&self, other: Reader<bool>) -> MapReader<bool> {
+        self.reader().or(other)
+    }
+    /// Same as `!&obs`
+    pub fn not(&self) -> MapReader<bool> {
+        !self.reader()
     }
 }
-impl<T> MapReader<T> {
-    pub fn on_updated(&self, cb: impl Fn() + 'static) -> Subscription {
-        self.listener_set.subscribe(cb)
+
+impl Not for &Observable<bool> {
+    type Output = MapReader<bool>;
+    fn not(self) -> MapReader<bool> {
+        !self.reader()
     }
-    pub fn force_notify(&self) {
-        self.listener_set.notify()
+}
+
+fn notify<T>(listener_set: &ListenerSet) -> usize {
+    debug!(
+        "notify {} ({} listeners)",
+        std::any::type_name::<T>(),
+        listener_set.len()
+    );
+    listener_set.notify_count()
+}
+
+/// Dropping the writer means there will be no further changes, which completion listeners are told about
+impl<T> Drop for Observable<T> {
+    fn drop(&mut self) {
+        self.listener_set.complete();
     }
 }
-impl<T: 'static> MapReader<T> {
-    pub fn new_dyn<F>(f: F) -> Self
-    where
-        F: Fn(&mut DynMapReaderContext) -> T + 'static,
-    {
-        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
 
-        let mut closure: Option<Rc<dyn Dispatch>> = None;
-        let value: Rc<Value<T>> = Rc::new_cyclic(|weak_value| {
-            let dyn_closure: Rc<DynMapClosure<T, F>> = {
-                let my_ls = listener_set.downgrade();
-                Rc::new_cyclic(move |weak_closure| DynMapClosure {
-                    value: weak_value.clone(),
-                    my_ls,
-                    dyn_downstreams: RefCell::default(),
-                    closure: weak_closure.clone(),
-                    f,
-                })
-            };
-            closure = Some(dyn_closure.clone());
-            let first_value = dyn_closure.calculate(false);
-            Value::new(first_value)
-        });
+impl<T> Default for Observable<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Observable::new(T::default())
+    }
+}
+
+impl<T: 'static> Reader<T> {
+    pub fn map_value<R: 'static>(self, f: impl Fn(&T) -> R + 'static) -> MapReader<R> {
+        use crate::map_obs;
+        let r = self;
+        map_obs!(f, r)
+    }
+
+    /// Like map_value, but the mapper only holds a weak reference to the source value. This means
+    /// the MapReader does not keep the source alive, and once the source is dropped the MapReader
+    /// stops updating and keeps its last value
+    pub fn map_value_weak<R: 'static>(self, f: impl Fn(&T) -> R + 'static) -> MapReader<R> {
+        let (source, source_listener_set) = self.split();
+        let value = Value::rc(f(&source.get()));
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+
+        let closure: Rc<dyn Dispatch> = {
+            let source = Rc::downgrade(&source);
+            let value = Rc::downgrade(&value);
+            let listener_set = listener_set.downgrade();
+            Rc::new(move || {
+                let Some(source) = source.upgrade() else {
+                    return;
+                };
+                let (Some(value), Some(listener_set)) = (value.upgrade(), listener_set.upgrade())
+                else {
+                    return;
+                };
+                value.set(f(&source.get()));
+                listener_set.notify();
+            })
+        };
+        if let Some(ls) = source_listener_set.upgrade() {
+            ls.subscribe_weak(Rc::downgrade(&closure));
+        }
+
+        MapReader::from((value, listener_set, closure))
+    }
+
+    /// Like map_value, for schedulers which decide for themselves when the work gets done. A change
+    /// to the source doesn't run the mapper, it marks the MapReader dirty and calls `schedule`, once
+    /// until the next recalculation. The value stays stale until recompute_if_dirty or recalculate
+    pub fn map_value_manual<R: 'static>(
+        self,
+        schedule: impl Fn() + 'static,
+        f: impl Fn(&T) -> R + 'static,
+    ) -> MapReader<R> {
+        let (source, source_listener_set) = self.split();
+        let value = Value::rc(f(&source.get()));
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+        let dirty: Rc<Cell<Option<usize>>> = Rc::default();
+
+        let recompute: Rc<dyn Dispatch> = {
+            let value = Rc::downgrade(&value);
+            let listener_set = listener_set.downgrade();
+            Rc::new(move || {
+                let (Some(value), Some(listener_set)) = (value.upgrade(), listener_set.upgrade())
+                else {
+                    return;
+                };
+                value.set(f(&source.get()));
+                listener_set.notify();
+            })
+        };
+        let closure: Rc<dyn Dispatch> = {
+            let dirty = dirty.clone();
+            let value = Rc::downgrade(&value);
+            Rc::new(move || {
+                let Some(value) = value.upgrade() else { return };
+                let generation = Some(value.generation());
+                if dirty.replace(generation) != generation {
+                    schedule()
+                }
+            })
+        };
+        if let Some(ls) = source_listener_set.upgrade() {
+            ls.subscribe_weak(Rc::downgrade(&closure));
+        }
 
         MapReader {
             value,
             listener_set,
-            closure: closure.unwrap(),
+            closure,
+            dirty,
+            recompute: Some(recompute),
         }
     }
-    pub fn recalculate(&self) {
-        self.closure.dispatch()
+
+    /// Map with a mapper which can fail. This is map_value with the mapper's type spelled out, which
+    /// gives a MapReader<Result<R, E>>, whose errors are also available separately, see MapReader::errors
+    pub fn try_map_value<R: 'static, E: 'static>(
+        self,
+        f: impl Fn(&T) -> Result<R, E> + 'static,
+    ) -> MapReader<Result<R, E>> {
+        self.map_value(f)
+    }
+
+    /// Like map_value, but only notifies when the mapped value actually changes
+    pub fn map_value_distinct<R: PartialEq + 'static>(
+        self,
+        f: impl Fn(&T) -> R + 'static,
+    ) -> MapReader<R> {
+        let (source, source_listener_set) = self.split();
+        let value = Value::rc(f(&source.get()));
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+
+        let closure: Rc<dyn Dispatch> = {
+            let value = Rc::downgrade(&value);
+            let listener_set = listener_set.downgrade();
+            Rc::new(move || {
+                let (Some(value), Some(listener_set)) = (value.upgrade(), listener_set.upgrade())
+                else {
+                    return;
+                };
+                let new_value = f(&source.get());
+                if *value.get() != new_value {
+                    value.set(new_value);
+                    listener_set.notify();
+                }
+            })
+        };
+        if let Some(ls) = source_listener_set.upgrade() {
+            ls.subscribe_weak(Rc::downgrade(&closure));
+        }
+
+        MapReader::from((value, listener_set, closure))
+    }
+
+    /// Pair the values of two readers, updating when either changes
+    pub fn zip<B: Clone + 'static>(self, other: Reader<B>) -> MapReader<(T, B)>
+    where
+        T: Clone,
+    {
+        MapReader::new_dyn(move |ctx| (ctx.track(&self).clone(), ctx.track(&other).clone()))
+    }
+
+    /// Accumulate over the values, starting with `f(&init, current)`, and then applying `f` to the
+    /// previous result and the new value on every change
+    pub fn scan<A: Clone + 'static>(
+        self,
+        init: A,
+        f: impl Fn(&A, &T) -> A + 'static,
+    ) -> MapReader<A> {
+        let acc = RefCell::new(init);
+        self.map_value(move |value: &T| {
+            let next = f(&acc.borrow(), value);
+            acc.replace(next.clone());
+            next
+        })
+    }
+
+    /// Follows the values which pass `pred`, keeping the last one which did while they don't. This is
+    /// None until a value has passed, including when the current value fails the predicate. Subscribers
+    /// are notified on every upstream change, but only ever see values which passed
+    pub fn filter(self, pred: impl Fn(&T) -> bool + 'static) -> MapReader<Option<T>>
+    where
+        T: Clone,
+    {
+        let retained: RefCell<Option<T>> = RefCell::new(None);
+        MapReader::new_dyn(move |ctx| {
+            let value = ctx.track(&self);
+            if pred(&value) {
+                retained.replace(Some(value.clone()));
+            }
+            retained.borrow().clone()
+        })
+    }
+
+    pub fn map_reader<R: Clone + 'static>(
+        self,
+        f: impl Fn(&T) -> Reader<R> + 'static,
+    ) -> MapReader<R> {
+        MapReader::new_dyn(move |ctx| {
+            let t = ctx.track(&self);
+            let r_reader = f(&t);
+            let r_val = ctx.track_dyn(&r_reader);
+            r_val.clone()
+        })
+    }
+
+    pub fn reader(self) -> Self {
+        self
     }
 }
 
-struct DynMapClosure<T, F> {
-    value: Weak<Value<T>>,
-    my_ls: WeakRef<ListenerSet>,
-    dyn_downstreams: Downstreams,
-    closure: Weak<DynMapClosure<T, F>>,
-    f: F,
+/// The logical combinators only notify when their result changes
+impl Reader<bool> {
+    pub fn and(self, other: Reader<bool>) -> MapReader<bool> {
+        self.combine_distinct(other, |a, b| *a && *b)
+    }
+    pub fn or(self, other: Reader<bool>) -> MapReader<bool> {
+        self.combine_distinct(other, |a, b| *a || *b)
+    }
 }
-impl<T: 'static, F> DynMapClosure<T, F>
-where
-    F: Fn(&mut DynMapReaderContext) -> T + 'static,
-{
-    fn calculate(&self, initilized: bool) -> T {
-        let mut ctx = DynMapReaderContext {
-            initilized,
-            closure: self.closure.clone(),
-            index: 0,
-            dyn_downstreams: &self.dyn_downstreams,
-        };
-        (self.f)(&mut ctx)
+
+impl Not for Reader<bool> {
+    type Output = MapReader<bool>;
+    fn not(self) -> MapReader<bool> {
+        self.map_value_distinct(|value| !*value)
     }
 }
-impl<T: 'static, F> Dispatch for DynMapClosure<T, F>
-where
-    F: Fn(&mut DynMapReaderContext) -> T + 'static,
-{
-    fn dispatch(&self) {
-        let Some(value) = self.value.upgrade() else {
-            return;
-        };
-        let Some(my_ls) = self.my_ls.upgrade() else {
-            return;
+
+impl Reader<f64> {
+    /// Follow the value, limited to the range from `min` to `max`. NaN is passed through as is.
+    /// Panics if `min` is greater than `max`, or either of them is NaN
+    pub fn clamp(self, min: f64, max: f64) -> MapReader<f64> {
+        assert!(
+            min <= max,
+            "clamp needs min <= max, got {} and {}",
+            min,
+            max
+        );
+        self.map_value_distinct(move |value| value.clamp(min, max))
+    }
+    /// The lesser of the two values. When one of them is NaN, the other is taken
+    pub fn min_with(self, other: Reader<f64>) -> MapReader<f64> {
+        self.combine_distinct(other, |a, b| a.min(*b))
+    }
+    /// The greater of the two values. When one of them is NaN, the other is taken
+    pub fn max_with(self, other: Reader<f64>) -> MapReader<f64> {
+        self.combine_distinct(other, |a, b| a.max(*b))
+    }
+}
+
+impl<T: 'static> Reader<T> {
+    /// Combine with another reader, notifying only when the result changes
+    fn combine_distinct<B: 'static, R: PartialEq + 'static>(
+        self,
+        other: Reader<B>,
+        f: impl Fn(&T, &B) -> R + 'static,
+    ) -> MapReader<R> {
+        let (a, a_listener_set) = self.split();
+        let (b, b_listener_set) = other.split();
+        let value = Value::rc(f(&a.get(), &b.get()));
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+
+        let closure: Rc<dyn Dispatch> = {
+            let value = Rc::downgrade(&value);
+            let listener_set = listener_set.downgrade();
+            Rc::new(move || {
+                let (Some(value), Some(listener_set)) = (value.upgrade(), listener_set.upgrade())
+                else {
+                    return;
+                };
+                let new_value = f(&a.get(), &b.get());
+                if *value.get() != new_value {
+                    value.set(new_value);
+                    listener_set.notify();
+                }
+            })
         };
-        let new_value = self.calculate(true);
+        for ls in [a_listener_set, b_listener_set] {
+            if let Some(ls) = ls.upgrade() {
+                ls.subscribe_weak(Rc::downgrade(&closure));
+            }
+        }
 
-        value.set(new_value);
-        my_ls.notify();
+        MapReader::from((value, listener_set, closure))
     }
 }
 
-/// Maps one or many observers into a new one
-/// ```
-/// use observable_rs::{Observable, map_obs};
-///
-/// let obs1: Observable<u32> = Observable::new(1);
-/// let obs2: Observable<u32> = Observable::new(2);
-///
-/// let obs = map_obs!(|a: &u32, b: &u32| {*a + *b}, obs1, obs2);
-/// assert_eq!(*obs.value(), 3);
-///
-/// obs1.set(3);
-/// assert_eq!(*obs.value(), 5);
-///
-/// obs2.set(4);
-/// assert_eq!(*obs.value(), 7);
-/// ```
-#[macro_export]
-macro_rules! map_obs {
-    ($cb:expr, $($obs:ident),+) => {{
-        use std::rc::Rc;
-        use $crate::unique_ref::{UniqueRef, WeakRef};
-        use $crate::{ListenerSet, Value, Reader, MapReader, Dispatch};
+impl<T: Clone + 'static> Reader<Reader<T>> {
+    /// Follow whichever reader this one currently holds. When it is swapped for another, the old
+    /// one is unsubscribed from
+    pub fn flatten(self) -> MapReader<T> {
+        MapReader::new_dyn(move |ctx| {
+            let inner = ctx.track(&self).clone();
+            let value = ctx.track_dyn(&inner).clone();
+            value
+        })
+    }
+}
 
-        let mut listener_set_list: Vec<WeakRef<ListenerSet>> = Vec::new();
+impl<T> Reader<T> {
+    pub fn value(&self) -> Ref<'_, T> {
+        self.value.get()
+    }
+    pub fn value_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.value.get().clone()
+    }
+    /// The number of live listeners on the Observable, if it is still alive
+    pub fn listener_count(&self) -> Option<usize> {
+        Some(self.listener_set.upgrade()?.len())
+    }
+    pub fn split(self) -> (Rc<Value<T>>, WeakRef<ListenerSet>) {
+        (self.value, self.listener_set)
+    }
+}
+impl<T: 'static> Reader<T> {
+    pub fn subscribe(&self, cb: impl Fn(&T) + 'static) -> Option<Subscription> {
+        let value = Rc::downgrade(&self.value);
+        let listener_set = self.listener_set.upgrade()?;
+        let sub = listener_set.subscribe(move || {
+            if let Some(value) = value.upgrade() {
+                cb(&value.get())
+            }
+        });
+        let type_name = std::any::type_name::<T>();
+        debug!(
+            "subscribe to {} ({} listeners)",
+            type_name,
+            listener_set.len()
+        );
+        Some(sub.named(type_name))
+    }
+    /// Like subscribe, but `cb` is first called with the current value, before this returns. This is
+    /// how a BehaviorSubject behaves. None if the Observable is gone, in which case `cb` is not called
+    pub fn subscribe_immediate(&self, cb: impl Fn(&T) + 'static) -> Option<Subscription> {
+        self.listener_set.upgrade()?;
+        cb(&self.value());
+        self.subscribe(cb)
+    }
+    pub fn once(&self, cb: impl FnOnce(&T) + 'static) -> Option<Subscription> {
+        let value = Rc::downgrade(&self.value);
+        let listener_set = self.listener_set.upgrade()?;
+        let sub = listener_set.once(move || {
+            if let Some(value) = value.upgrade() {
+                cb(&value.get())
+            }
+        });
+        let type_name = std::any::type_name::<T>();
+        debug!(
+            "subscribe once to {} ({} listeners)",
+            type_name,
+            listener_set.len()
+        );
+        Some(sub.named(type_name))
+    }
+}
+impl<T: 'static> Observe<T> for Observable<T> {
+    fn value_ref(&self) -> Ref<'_, T> {
+        self.value()
+    }
+    fn subscribe(&self, cb: Box<dyn Fn(&T)>) -> Subscription {
+        Observable::subscribe(self, cb)
+    }
+    fn once(&self, cb: Box<dyn FnOnce(&T)>) -> Subscription {
+        Observable::once(self, cb)
+    }
+}
+/// A Reader whose Observable is gone gets a subscription which never fires
+impl<T: 'static> Observe<T> for Reader<T> {
+    fn value_ref(&self) -> Ref<'_, T> {
+        self.value()
+    }
+    fn subscribe(&self, cb: Box<dyn Fn(&T)>) -> Subscription {
+        Reader::subscribe(self, cb).unwrap_or_else(inert_subscription)
+    }
+    fn once(&self, cb: Box<dyn FnOnce(&T)>) -> Subscription {
+        Reader::once(self, cb).unwrap_or_else(inert_subscription)
+    }
+}
+fn inert_subscription() -> Subscription {
+    Subscription::new(Rc::new(|| {}))
+}
+impl<T: 'static> Reader<T> {
+    /// Like once, for "ready or not" semantics: if the Observable is already gone, there will be no
+    /// next change, so `cb` is called right away with its last value and None is returned
+    pub fn once_or_current(&self, cb: impl FnOnce(&T) + 'static) -> Option<Subscription> {
+        if self.listener_set.upgrade().is_none() {
+            cb(&self.value());
+            return None;
+        }
+        self.once(cb)
+    }
+    /// Subscribe, but only fire on every nth change. An `n` of zero is treated as one
+    pub fn sample_every(&self, n: usize, cb: impl Fn(&T) + 'static) -> Option<Subscription> {
+        let n = n.max(1);
+        let count = Cell::new(0usize);
+        self.subscribe(move |value| {
+            count.set(count.get() + 1);
+            if count.get() == n {
+                count.set(0);
+                cb(value)
+            }
+        })
+    }
+}
+
+/// Keeps an effect running. The effect stops when this is dropped
+pub struct EffectHandle {
+    #[allow(dead_code)]
+    sub: Option<Subscription>,
+}
+
+impl<T: 'static> Reader<T> {
+    /// Run a side effect with the current value, and again on every change, without
+    /// producing a derived value (like Solid's createEffect)
+    pub fn effect(self, f: impl Fn(&T) + 'static) -> EffectHandle {
+        f(&self.value());
+        EffectHandle {
+            sub: self.subscribe(f),
+        }
+    }
+}
+
+impl<T> Reader<T> {
+    pub fn on_updated(&self, cb: impl Fn() + 'static) -> Option<Subscription> {
+        let sub = self.listener_set.upgrade()?.subscribe(cb);
+        Some(sub)
+    }
+    /// Fires once the Observable is dropped. None if it is already gone
+    pub fn on_complete(&self, cb: impl FnOnce() + 'static) -> Option<Subscription> {
+        let sub = self.listener_set.upgrade()?.on_complete(cb);
+        Some(sub)
+    }
+    pub fn force_notify(&self) {
+        if let Some(ls) = self.listener_set.upgrade() {
+            ls.notify()
+        }
+    }
+}
+impl<T> Clone for Reader<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            listener_set: self.listener_set.clone(),
+        }
+    }
+}
+
+/// MapReader is a handle to the latest output value of a map function.
+/// It is essential the the MapReader (or its clones) be the only struct to have a strong reference
+/// to said closure. This is because we want the closure to be able to close over owned/strong references
+/// to its upstream Readers WITHOUT creating a cycle.
+///
+/// The weak ref must be between the Observable and the map function. NOT inside the map function
+pub struct MapReader<T> {
+    value: Rc<Value<T>>,
+    listener_set: UniqueRef<ListenerSet>,
+    #[allow(dead_code, clippy::type_complexity)]
+    closure: Rc<dyn Dispatch>,
+    /// The generation of the value when it was marked dirty, see mark_dirty. Any recalculation
+    /// since then moves the value on to another generation, which clears it
+    dirty: Rc<Cell<Option<usize>>>,
+    /// The mapper, for a MapReader which is recalculated manually, see map_value_manual. Otherwise
+    /// `closure` is the mapper
+    recompute: Option<Rc<dyn Dispatch>>,
+}
+
+/// A MapReader which holds off on calculating its value until it is first needed, see map_obs_lazy.
+/// Anything which reads the value or listens for changes to it calculates it first, including
+/// handing out a Reader
+pub struct LazyMapReader<T> {
+    /// Filled in by the first calculation, after which it is shared with Readers like a MapReader's
+    value: Rc<OnceCell<Rc<Value<T>>>>,
+    calc: Rc<dyn Fn() -> T>,
+    listener_set: UniqueRef<ListenerSet>,
+    #[allow(dead_code)]
+    closure: Rc<dyn Dispatch>,
+}
+
+impl<T: 'static> LazyMapReader<T> {
+    /// Recalculate with `calc` whenever any of the `upstream` listener sets notify, once the value
+    /// has been calculated for the first time
+    pub fn new(upstream: Vec<WeakRef<ListenerSet>>, calc: impl Fn() -> T + 'static) -> Self {
+        let value: Rc<OnceCell<Rc<Value<T>>>> = Rc::default();
+        let calc: Rc<dyn Fn() -> T> = Rc::new(calc);
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+
+        let closure: Rc<dyn Dispatch> = {
+            let value = Rc::downgrade(&value);
+            let listener_set = listener_set.downgrade();
+            let calc = calc.clone();
+            Rc::new(move || {
+                let value = value.upgrade()?;
+                // Not read yet, so the first read calculates it from scratch anyway
+                value.get()?.set(calc());
+                listener_set.upgrade()?.notify();
+                Some(())
+            })
+        };
+        for ls in upstream {
+            if let Some(ls) = ls.upgrade() {
+                ls.subscribe_weak(Rc::downgrade(&closure));
+            }
+        }
+
+        LazyMapReader {
+            value,
+            calc,
+            listener_set,
+            closure,
+        }
+    }
+    pub fn subscribe(&self, cb: impl Fn(&T) + 'static) -> Subscription {
+        self.reader().subscribe(cb).unwrap()
+    }
+    pub fn once(&self, cb: impl FnOnce(&T) + 'static) -> Subscription {
+        self.reader().once(cb).unwrap()
+    }
+}
+
+impl<T> LazyMapReader<T> {
+    /// Whether the value has been calculated yet
+    pub fn is_computed(&self) -> bool {
+        self.value.get().is_some()
+    }
+    pub fn value(&self) -> Ref<'_, T> {
+        self.compute().get()
+    }
+    pub fn value_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.value().clone()
+    }
+    pub fn reader(&self) -> Reader<T> {
+        Reader {
+            value: self.compute().clone(),
+            listener_set: self.listener_set.downgrade(),
+        }
+    }
+    pub fn on_updated(&self, cb: impl Fn() + 'static) -> Subscription {
+        self.compute();
+        self.listener_set.subscribe(cb)
+    }
+    pub fn force_notify(&self) {
+        self.listener_set.notify()
+    }
+    fn compute(&self) -> &Rc<Value<T>> {
+        self.value.get_or_init(|| Value::rc((self.calc)()))
+    }
+}
+
+impl<T> From<(Rc<Value<T>>, UniqueRef<ListenerSet>, Rc<dyn Dispatch>)> for MapReader<T> {
+    fn from(
+        (value, listener_set, closure): (Rc<Value<T>>, UniqueRef<ListenerSet>, Rc<dyn Dispatch>),
+    ) -> Self {
+        MapReader {
+            value,
+            listener_set,
+            closure,
+            dirty: Rc::default(),
+            recompute: None,
+        }
+    }
+}
+impl<T> MapReader<T> {
+    pub fn value(&self) -> Ref<'_, T> {
+        self.value.get()
+    }
+    pub fn value_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.value.get().clone()
+    }
+
+    pub fn reader(&self) -> Reader<T> {
+        Reader {
+            value: self.value.clone(),
+            listener_set: self.listener_set.downgrade(),
+        }
+    }
+    pub fn listener_set(&self) -> WeakRef<ListenerSet> {
+        self.listener_set.downgrade()
+    }
+}
+impl<T: 'static> MapReader<T> {
+    pub fn subscribe(&self, cb: impl Fn(&T) + 'static) -> Subscription {
+        self.reader().subscribe(cb).unwrap()
+    }
+    pub fn once(&self, cb: impl FnOnce(&T) + 'static) -> Subscription {
+        self.reader().once(cb).unwrap()
+    }
+}
+impl<R: 'static, E: Clone + 'static> MapReader<Result<R, E>> {
+    /// The latest mapping error, which is cleared once the mapper succeeds again. This is a MapReader
+    /// rather than a Reader because a Reader doesn't keep a mapping alive, so the errors would stop
+    /// updating right away. Keep it for as long as the errors are wanted, and call reader() on it to
+    /// hand out Readers
+    pub fn errors(&self) -> MapReader<Option<E>> {
+        self.reader()
+            .map_value(|result: &Result<R, E>| result.as_ref().err().cloned())
+    }
+}
+impl<T> MapReader<T> {
+    /// Run the mapper again, notifying subscribers
+    pub fn recalculate(&self) {
+        self.dirty.set(None);
+        match &self.recompute {
+            Some(recompute) => recompute.dispatch(),
+            None => self.closure.dispatch(),
+        }
+    }
+    /// Note that the value needs recalculating, without doing so yet. A MapReader made with
+    /// map_value_manual is marked dirty by changes to its source, see recompute_if_dirty
+    pub fn mark_dirty(&self) {
+        self.dirty.set(Some(self.value.generation()))
+    }
+    /// Whether the value was marked dirty, and hasn't been recalculated since. Changes to the sources
+    /// of an ordinary MapReader recalculate it, which also counts
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.get() == Some(self.value.generation())
+    }
+    /// Recalculate if marked dirty since the last recalculation, returning whether it did
+    pub fn recompute_if_dirty(&self) -> bool {
+        if !self.is_dirty() {
+            return false;
+        }
+        self.recalculate();
+        true
+    }
+    pub fn on_updated(&self, cb: impl Fn() + 'static) -> Subscription {
+        self.listener_set.subscribe(cb)
+    }
+    pub fn force_notify(&self) {
+        self.listener_set.notify()
+    }
+}
+impl<T: 'static> MapReader<T> {
+    pub fn new_dyn<F>(f: F) -> Self
+    where
+        F: Fn(&mut DynMapReaderContext) -> T + 'static,
+    {
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+
+        let mut closure: Option<Rc<dyn Dispatch>> = None;
+        let value: Rc<Value<T>> = Rc::new_cyclic(|weak_value| {
+            let dyn_closure: Rc<DynMapClosure<T, F>> = {
+                let my_ls = listener_set.downgrade();
+                Rc::new_cyclic(move |weak_closure| DynMapClosure {
+                    value: weak_value.clone(),
+                    my_ls,
+                    dyn_downstreams: RefCell::default(),
+                    closure: weak_closure.clone(),
+                    f,
+                })
+            };
+            closure = Some(dyn_closure.clone());
+            let first_value = dyn_closure.calculate(false);
+            Value::new(first_value)
+        });
+
+        MapReader {
+            value,
+            listener_set,
+            closure: closure.unwrap(),
+            dirty: Rc::default(),
+            recompute: None,
+        }
+    }
+}
+
+struct DynMapClosure<T, F> {
+    value: Weak<Value<T>>,
+    my_ls: WeakRef<ListenerSet>,
+    dyn_downstreams: Downstreams,
+    closure: Weak<DynMapClosure<T, F>>,
+    f: F,
+}
+impl<T: 'static, F> DynMapClosure<T, F>
+where
+    F: Fn(&mut DynMapReaderContext) -> T + 'static,
+{
+    fn calculate(&self, initilized: bool) -> T {
+        let mut ctx = DynMapReaderContext {
+            initilized,
+            closure: self.closure.clone(),
+            index: 0,
+            dyn_downstreams: &self.dyn_downstreams,
+        };
+        (self.f)(&mut ctx)
+    }
+}
+impl<T: 'static, F> Dispatch for DynMapClosure<T, F>
+where
+    F: Fn(&mut DynMapReaderContext) -> T + 'static,
+{
+    fn dispatch(&self) {
+        let Some(value) = self.value.upgrade() else {
+            return;
+        };
+        let Some(my_ls) = self.my_ls.upgrade() else {
+            return;
+        };
+        let new_value = self.calculate(true);
+
+        value.set(new_value);
+        my_ls.notify();
+    }
+}
+
+/// Like map_obs!, for when the number of readers is only known at runtime. `f` is called with the
+/// current values of all of the readers, in order, whenever any of them changes
+pub fn combine_latest<T: Clone + 'static, R: 'static>(
+    readers: Vec<Reader<T>>,
+    f: impl Fn(&[T]) -> R + 'static,
+) -> MapReader<R> {
+    MapReader::new_dyn(move |ctx| {
+        let values: Vec<T> = readers
+            .iter()
+            .map(|reader| ctx.track(reader).clone())
+            .collect();
+        f(&values)
+    })
+}
+
+/// Like map_obs, but the values aren't combined until the result is first read or subscribed to.
+/// Changes made before then don't call the combiner either
+/// ```
+/// use observable_rs::{Observable, map_obs_lazy};
+///
+/// let obs1: Observable<u32> = Observable::new(1);
+/// let obs2: Observable<u32> = Observable::new(2);
+///
+/// let obs = map_obs_lazy!(|a: &u32, b: &u32| {*a + *b}, obs1, obs2);
+/// assert!(!obs.is_computed());
+///
+/// obs1.set(3);
+/// assert_eq!(*obs.value(), 5);
+/// ```
+#[macro_export]
+macro_rules! map_obs_lazy {
+    ($cb:expr, $($obs:ident),+) => {{
+        use $crate::unique_ref::WeakRef;
+        use $crate::{ListenerSet, Reader, LazyMapReader};
+
+        let mut listener_set_list: Vec<WeakRef<ListenerSet>> = Vec::new();
+
+        $(let $obs = {
+            let reader: Reader<_> = $obs.reader();
+            let (value, listener_set) = reader.split();
+            listener_set_list.push(listener_set);
+            value
+        };)+
+        let cb = $cb;
+        let calc = move || cb($(&*$obs.get(),)*);
+
+        LazyMapReader::new(listener_set_list, calc)
+    }};
+}
+
+/// Maps one or many observers into a new one
+/// ```
+/// use observable_rs::{Observable, map_obs};
+///
+/// let obs1: Observable<u32> = Observable::new(1);
+/// let obs2: Observable<u32> = Observable::new(2);
+///
+/// let obs = map_obs!(|a: &u32, b: &u32| {*a + *b}, obs1, obs2);
+/// assert_eq!(*obs.value(), 3);
+///
+/// obs1.set(3);
+/// assert_eq!(*obs.value(), 5);
+///
+/// obs2.set(4);
+/// assert_eq!(*obs.value(), 7);
+/// ```
+#[macro_export]
+macro_rules! map_obs {
+    ($cb:expr, $($obs:ident),+) => {{
+        use std::rc::Rc;
+        use $crate::unique_ref::{UniqueRef, WeakRef};
+        use $crate::{ListenerSet, Value, Reader, MapReader, Dispatch};
+
+        let mut listener_set_list: Vec<WeakRef<ListenerSet>> = Vec::new();
+
+        $(let $obs = {
+            let reader: Reader<_> = $obs.reader();
+            let (value, listener_set) = reader.split();
+            listener_set_list.push(listener_set);
+            value
+        };)+
+        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
+        #[allow(clippy::redundant_closure_call)]
+        let calc = move || $cb($(&*$obs.get(),)*);
+        let value = calc();
+        let value = Value::rc(value);
+
+        let closure: Rc<dyn Dispatch> = {
+            let listener_set = listener_set.downgrade();
+            let value = Rc::downgrade(&value);
+
+            Rc::new(move || {
+                let reader_value = value.upgrade()?;
+                let listener_set = listener_set.upgrade()?;
+
+                reader_value.set(calc());
+                listener_set.notify();
+                Some(())
+            })
+        };
+        let weak_closure = Rc::downgrade(&closure);
+        for ls in listener_set_list.into_iter() {
+            if let Some(listener_set) = ls.upgrade() {
+                let closure = weak_closure.clone();
+                listener_set.subscribe_weak(closure);
+            }
+        }
+        let closure: Rc<dyn Dispatch> = closure.clone();
+
+        MapReader::from((value, listener_set, closure))
+    }};
+}
+
+pub struct DynMapReaderContext<'a> {
+    index: usize,
+    dyn_downstreams: &'a Downstreams,
+    initilized: bool,
+    closure: Weak<dyn Dispatch>,
+}
+/// A tracked reader is identified by both its value and its listener set. Either one
+/// may be swapped independently, and we need to resubscribe in both cases
+type DownstreamKey = (*const (), *const ListenerSet);
+type Downstreams = RefCell<Vec<(DownstreamKey, Option<Subscription>)>>;
+
+impl<'ctx> DynMapReaderContext<'ctx> {
+    fn track_dyn_reader(&mut self, value_ptr: *const (), listener_set: &WeakRef<ListenerSet>) {
+        let index = self.index;
+        let key = (value_ptr, listener_set.as_ptr());
+        let mut list = self.dyn_downstreams.borrow_mut();
+
+        if index < list.len() {
+            if key != list[index].0 {
+                let cb = self.subscription_closure();
+                let sub = listener_set.upgrade().map(|ls| ls.subscribe(cb));
+                list[index] = (key, sub);
+            }
+        } else {
+            let cb = self.subscription_closure();
+            let sub = listener_set.upgrade().map(|ls| ls.subscribe(cb));
+            list.push((key, sub))
+        }
+        self.index += 1;
+    }
+    #[inline]
+    pub fn track_dyn<'a, V>(&mut self, reader: &'a Reader<V>) -> Ref<'a, V> {
+        let value_ptr = Rc::as_ptr(&reader.value) as *const ();
+        self.track_dyn_reader(value_ptr, &reader.listener_set);
+        reader.value.get()
+    }
+
+    pub fn track_reader(&self, listener_set: &WeakRef<ListenerSet>) {
+        if !self.initilized {
+            if let Some(ls) = listener_set.upgrade() {
+                ls.subscribe_weak(self.closure.clone())
+            }
+        }
+    }
+    #[inline]
+    pub fn track<'a, V>(&self, reader: &'a Reader<V>) -> Ref<'a, V> {
+        self.track_reader(&reader.listener_set);
+        reader.value.get()
+    }
+}
+
+impl<'ctx> DynMapReaderContext<'ctx> {
+    fn clear_unused_readers(&self) {
+        let mut list = self.dyn_downstreams.borrow_mut();
+        let new_len = self.index;
+        list.truncate(new_len);
+    }
+}
+impl<'ctx> Drop for DynMapReaderContext<'ctx> {
+    fn drop(&mut self) {
+        self.clear_unused_readers();
+    }
+}
+impl<'ctx> DynMapReaderContext<'ctx> {
+    fn subscription_closure(&self) -> impl Dispatch + 'static {
+        let cb = self.closure.clone();
+        move || {
+            if let Some(f) = cb.upgrade() {
+                f.dispatch();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        rc::Rc,
+    };
+
+    use crate::{MoveEvent, Observe, Pushable, Reader, RingBuffer, Subscription, TextDiff};
+
+    use super::Observable;
+
+    #[test]
+    fn observable_vec_push() {
+        let obs = Observable::new(vec![1, 2, 3]);
+
+        let counter: Rc<RefCell<Option<usize>>> = Rc::new(RefCell::new(None));
+
+        let _sub = {
+            let counter = counter.clone();
+            obs.subscribe(move |v: &Vec<u32>| {
+                counter.replace(Some(v.len()));
+            })
+        };
+
+        assert_eq!(*counter.borrow(), None);
+        obs.push(0);
+        assert_eq!(*counter.borrow(), Some(4));
+    }
+
+    #[test]
+    fn observable_vec_diff_subscribe() {
+        let obs = Observable::new(vec![1, 2, 3]);
+
+        type Diff = (Vec<u32>, Vec<u32>);
+        let diffs: Rc<RefCell<Vec<Diff>>> = Rc::default();
+        let _sub = {
+            let diffs = diffs.clone();
+            obs.diff_subscribe(move |added, removed| {
+                diffs.borrow_mut().push((added.to_vec(), removed.to_vec()));
+            })
+        };
+
+        obs.push(4);
+        obs.set(vec![2, 4, 5]);
+        // Reordering alone is not a change
+        obs.set(vec![5, 4, 2]);
+
+        assert_eq!(
+            *diffs.borrow(),
+            vec![(vec![4], vec![]), (vec![5], vec![1, 3])]
+        );
+    }
+
+    #[test]
+    fn replace_returns_previous() {
+        let obs = Observable::new(String::from("old"));
+        let seen: Rc<RefCell<Vec<String>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            let reader = obs.reader();
+            obs.subscribe(move |_| seen.borrow_mut().push(reader.value_cloned()))
+        };
+
+        assert_eq!(obs.replace("new".into()), "old");
+        assert_eq!(*obs.value(), "new");
+        assert_eq!(*seen.borrow(), vec!["new".to_string()]);
+    }
+
+    #[test]
+    fn set_if_changed() {
+        let obs = Observable::new(1);
+        let notified = Rc::new(Cell::new(0));
+        let _sub = {
+            let notified = notified.clone();
+            obs.subscribe(move |_| notified.set(notified.get() + 1))
+        };
+
+        assert!(!obs.set_if_changed(1));
+        assert_eq!(notified.get(), 0);
+
+        assert!(obs.set_if_changed(2));
+        assert_eq!(*obs.value(), 2);
+        assert_eq!(notified.get(), 1);
+    }
+
+    #[test]
+    fn update_in_place() {
+        struct Todo {
+            tags: Vec<&'static str>,
+        }
+        let obs = Observable::new(Todo { tags: vec!["a"] });
+        let seen: Rc<RefCell<Vec<usize>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            let reader = obs.reader();
+            // Reading the value while being notified must not conflict with the update
+            obs.subscribe(move |_| seen.borrow_mut().push(reader.value().tags.len()))
+        };
+
+        obs.update(|todo| todo.tags.push("b"));
+        assert_eq!(obs.value().tags, vec!["a", "b"]);
+        assert_eq!(*seen.borrow(), vec![2]);
+    }
+
+    #[test]
+    fn len_reader_ignores_in_place_changes() {
+        let obs = Observable::new(vec![1, 2, 3]);
+        let len = obs.len_reader();
+        let notified = Rc::new(Cell::new(0));
+        let _sub = {
+            let notified = notified.clone();
+            len.subscribe(move |_| notified.set(notified.get() + 1))
+        };
+
+        assert!(obs.modify_at(1, |item| *item = 20));
+        assert_eq!(*obs.value(), vec![1, 20, 3]);
+        assert!(!obs.modify_at(3, |item| *item = 40));
+        assert_eq!(notified.get(), 0);
+
+        obs.push(4);
+        assert_eq!(*len.value(), 4);
+        assert_eq!(notified.get(), 1);
+    }
+
+    #[test]
+    fn flatten_follows_inner_observables() {
+        let list = Observable::new(vec![Observable::new(1), Observable::new(2)]);
+        let flat = list.flatten();
+        assert_eq!(*flat.value(), vec![1, 2]);
+
+        list.value()[0].set(10);
+        assert_eq!(*flat.value(), vec![10, 2]);
+
+        list.push(Observable::new(3));
+        assert_eq!(*flat.value(), vec![10, 2, 3]);
+        list.value()[2].set(30);
+        assert_eq!(*flat.value(), vec![10, 2, 30]);
+
+        // Keep the removed observable alive, to check that it was unsubscribed from
+        let removed = RefCell::new(None);
+        list.update(|items| *removed.borrow_mut() = Some(items.remove(0)));
+        let removed = removed.into_inner().unwrap();
+        assert_eq!(*flat.value(), vec![2, 30]);
+        list.value()[0].set(20);
+        assert_eq!(*flat.value(), vec![20, 30]);
+
+        let notified = Rc::new(Cell::new(0));
+        let _sub = {
+            let notified = notified.clone();
+            flat.subscribe(move |_| notified.set(notified.get() + 1))
+        };
+        assert_eq!(removed.listener_count(), 0);
+        removed.set(100);
+        assert_eq!(notified.get(), 0);
+        assert_eq!(*flat.value(), vec![20, 30]);
+    }
+
+    #[test]
+    fn subscribe_immediate_fires_with_initial_value() {
+        let obs = Observable::new(1);
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            obs.subscribe_immediate(move |v| seen.borrow_mut().push(*v))
+        };
+        assert_eq!(*seen.borrow(), vec![1]);
+
+        obs.set(2);
+        assert_eq!(*seen.borrow(), vec![1, 2]);
+    }
+
+    #[test]
+    fn once_or_current_after_writer_dropped() {
+        let obs = Observable::new(1);
+        let reader = obs.reader();
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+
+        // While the writer is alive, this waits for the next change
+        let sub = {
+            let seen = seen.clone();
+            reader.once_or_current(move |v| seen.borrow_mut().push(*v))
+        };
+        assert!(sub.is_some());
+        obs.set(2);
+        assert_eq!(*seen.borrow(), vec![2]);
+
+        drop(obs);
+        let sub = {
+            let seen = seen.clone();
+            reader.once_or_current(move |v| seen.borrow_mut().push(*v))
+        };
+        assert!(sub.is_none());
+        assert_eq!(*seen.borrow(), vec![2, 2]);
+    }
+
+    #[test]
+    fn zip_pairs_latest_values() {
+        let a = Observable::new(1);
+        let b = Observable::new("one");
+        let zipped = a.reader().zip(b.reader());
+        assert_eq!(*zipped.value(), (1, "one"));
+
+        a.set(2);
+        assert_eq!(*zipped.value(), (2, "one"));
+        b.set("two");
+        assert_eq!(*zipped.value(), (2, "two"));
+    }
+
+    #[test]
+    fn scan_running_total() {
+        let obs = Observable::new(1u32);
+        let total = obs.reader().scan(0, |acc, v| acc + v);
+        assert_eq!(*total.value(), 1);
+        obs.set(2);
+        assert_eq!(*total.value(), 3);
+        obs.set(3);
+        assert_eq!(*total.value(), 6);
+    }
+
+    #[test]
+    fn filter_passes_even_values() {
+        let obs = Observable::new(1);
+        let even = obs.reader().filter(|v| v % 2 == 0);
+        // The first value fails the predicate
+        assert_eq!(*even.value(), None);
+
+        let seen: Rc<RefCell<Vec<Option<u32>>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            even.subscribe(move |v| seen.borrow_mut().push(*v))
+        };
+        for v in 2..=5 {
+            obs.set(v);
+        }
+        assert_eq!(*even.value(), Some(4));
+        assert_eq!(*seen.borrow(), vec![Some(2), Some(2), Some(4), Some(4)]);
+    }
+
+    #[test]
+    fn string_diff_subscribe() {
+        let obs = Observable::new(String::from("hello world"));
+        let diffs: Rc<RefCell<Vec<TextDiff>>> = Rc::default();
+        let _sub = {
+            let diffs = diffs.clone();
+            obs.diff_subscribe(move |diff| diffs.borrow_mut().push(diff.clone()))
+        };
+
+        obs.set("hello brave world".into());
+        obs.set("hello brave world".into());
+        obs.set("hello world".into());
+        obs.set("héllo world".into());
+
+        assert_eq!(
+            *diffs.borrow(),
+            vec![
+                TextDiff {
+                    range: 6..6,
+                    inserted: "brave ".into()
+                },
+                TextDiff {
+                    range: 6..12,
+                    inserted: "".into()
+                },
+                TextDiff {
+                    range: 1..2,
+                    inserted: "é".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn flatten_nested_reader() {
+        let a = Observable::new(1);
+        let b = Observable::new(10);
+        let selected = Observable::new(a.reader());
+        let flat = selected.reader().flatten();
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            flat.subscribe(move |v| seen.borrow_mut().push(*v))
+        };
+
+        a.set(2);
+        selected.set(b.reader());
+        assert_eq!(*flat.value(), 10);
+
+        // The old inner reader is no longer followed
+        a.set(3);
+        b.set(11);
+        assert_eq!(*flat.value(), 11);
+        assert_eq!(*seen.borrow(), vec![2, 10, 11]);
+    }
+
+    #[test]
+    fn reentrant_map_obs_is_not_torn() {
+        let a = Observable::new(1u32);
+        let b = map_obs!(|a: &u32| *a + 1, a);
+        let c = Rc::new(Observable::new(10u32));
+        // c is kept at ten times a, by a subscriber rather than a mapper
+        let _sync = {
+            let c = c.clone();
+            a.subscribe(move |a| c.set(*a * 10))
+        };
+        let d = map_obs!(|b: &u32, c: &u32| (*b, *c), b, c);
+
+        let seen: Rc<RefCell<Vec<(u32, u32)>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            d.subscribe(move |d| seen.borrow_mut().push(*d))
+        };
+
+        a.set(2);
+        assert_eq!(*d.value(), (3, 20));
+        // d is only recomputed once both b and c have caught up with a
+        assert_eq!(*seen.borrow(), vec![(3, 20)]);
+    }
+
+    #[test]
+    fn subscriber_reads_fresh_derived_value() {
+        let a = Observable::new(1u32);
+        let source = Rc::new(Observable::new(0u32));
+        let derived = Rc::new(map_obs!(|v: &u32| *v * 2, source));
+
+        let read: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let source = source.clone();
+            let derived = derived.clone();
+            let read = read.clone();
+            a.subscribe(move |a| {
+                source.set(*a * 5);
+                read.borrow_mut().push(*derived.value());
+            })
+        };
+
+        a.set(2);
+        assert_eq!(*read.borrow(), vec![20]);
+    }
+
+    #[test]
+    fn notifications_resume_after_listener_panics() {
+        let obs = Observable::new(0);
+        let _panics = obs.subscribe(|v| {
+            if *v == 1 {
+                panic!("listener failed");
+            }
+        });
+        let other = Rc::new(Observable::new(0));
+        let _forward = {
+            let other = other.clone();
+            obs.subscribe(move |v| other.set(*v))
+        };
+        let seen: Rc<RefCell<Vec<i32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            other.subscribe(move |v| seen.borrow_mut().push(*v))
+        };
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| obs.set(1)));
+        assert!(result.is_err());
+
+        // Neither the dispatch nor its batch are left running, so nothing is held back any more
+        obs.set(2);
+        other.set(3);
+        assert_eq!(*seen.borrow(), vec![2, 3]);
+        assert_eq!(obs.set_and_count(4), 2);
+    }
+
+    #[test]
+    fn move_item_reports_move() {
+        let obs = Observable::new(vec!['a', 'b', 'c', 'd']);
+        let moves: Rc<RefCell<Vec<MoveEvent>>> = Rc::default();
+        let _sub = {
+            let moves = moves.clone();
+            obs.subscribe_moves(move |event| moves.borrow_mut().push(*event))
+        };
+
+        obs.move_item(0, 2);
+        assert_eq!(*obs.value(), vec!['b', 'c', 'a', 'd']);
+        obs.move_item(3, 1);
+        assert_eq!(*obs.value(), vec!['b', 'd', 'c', 'a']);
+        // Out of bounds, and not a move
+        obs.move_item(0, 4);
+        obs.push('e');
+
+        assert_eq!(
+            *moves.borrow(),
+            vec![MoveEvent { from: 0, to: 2 }, MoveEvent { from: 3, to: 1 }]
+        );
+    }
+
+    #[test]
+    fn subscription_bag() {
+        let a = Observable::new(0);
+        let b = Observable::new(0);
+        let calls = Rc::new(Cell::new(0));
+
+        let bag = crate::SubscriptionBag::new();
+        for obs in [&a, &b] {
+            let calls = calls.clone();
+            obs.subscribe_into(&bag, move |_| calls.set(calls.get() + 1));
+        }
+        {
+            let calls = calls.clone();
+            bag.add(a.subscribe(move |_| calls.set(calls.get() + 1)));
+        }
+        assert_eq!(bag.len(), 3);
+
+        a.set(1);
+        b.set(1);
+        assert_eq!(calls.get(), 3);
+
+        drop(bag);
+        a.set(2);
+        b.set(2);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn cancellation_token_group() {
+        let a = Observable::new(0);
+        let b = Observable::new(String::new());
+        let calls = Rc::new(Cell::new(0));
+
+        let token = crate::CancellationToken::new();
+        {
+            let calls = calls.clone();
+            a.subscribe_with_token(&token, move |_| calls.set(calls.get() + 1));
+        }
+        {
+            let calls = calls.clone();
+            b.subscribe_with_token(&token.clone(), move |_| calls.set(calls.get() + 1));
+        }
+        a.set(1);
+        b.set("one".into());
+        assert_eq!(calls.get(), 2);
+
+        token.cancel();
+        a.set(2);
+        b.set("two".into());
+        assert_eq!(calls.get(), 2);
+
+        // Subscribing with a canceled token is a no-op
+        {
+            let calls = calls.clone();
+            a.subscribe_with_token(&token, move |_| calls.set(calls.get() + 1));
+        }
+        a.set(3);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn batch_scope_notifies_once() {
+        let obs: Observable<Vec<u32>> = Observable::default();
+        let mapped = obs.map_value(|v| v.len());
+        let seen: Rc<RefCell<Vec<usize>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            mapped.subscribe(move |len| seen.borrow_mut().push(*len))
+        };
+
+        crate::batch_scope(|| {
+            obs.push(1);
+            crate::batch_scope(|| obs.push(2));
+            obs.push(3);
+            assert!(seen.borrow().is_empty());
+        });
+        assert_eq!(*seen.borrow(), vec![3]);
+
+        obs.push(4);
+        assert_eq!(*seen.borrow(), vec![3, 4]);
+    }
+
+    #[test]
+    fn observe_is_generic_over_observable_and_reader() {
+        fn record<O: Observe<i32>>(o: &O, seen: &Rc<RefCell<Vec<i32>>>) -> Subscription {
+            seen.borrow_mut().push(*o.value_ref());
+            let seen = seen.clone();
+            o.subscribe(Box::new(move |v| seen.borrow_mut().push(*v)))
+        }
+
+        let obs = Observable::new(1);
+        let from_obs: Rc<RefCell<Vec<i32>>> = Rc::default();
+        let from_reader: Rc<RefCell<Vec<i32>>> = Rc::default();
+        let _a = record(&obs, &from_obs);
+        let _b = record(&obs.reader(), &from_reader);
+
+        obs.set(2);
+        assert_eq!(*from_obs.borrow(), vec![1, 2]);
+        assert_eq!(*from_reader.borrow(), vec![1, 2]);
+    }
+
+    #[test]
+    fn map_obs_lazy_defers_first_calculation() {
+        let a = Observable::new(1);
+        let b = Observable::new(2);
+        let calls = Rc::new(Cell::new(0));
+        let sum = {
+            let calls = calls.clone();
+            crate::map_obs_lazy!(
+                move |a: &i32, b: &i32| {
+                    calls.set(calls.get() + 1);
+                    a + b
+                },
+                a,
+                b
+            )
+        };
+        a.set(10);
+        assert_eq!(calls.get(), 0);
+
+        assert_eq!(*sum.value(), 12);
+        assert_eq!(calls.get(), 1);
+
+        let seen = Rc::new(Cell::new(0));
+        let _sub = {
+            let seen = seen.clone();
+            sum.subscribe(move |v| seen.set(*v))
+        };
+        b.set(5);
+        assert_eq!(seen.get(), 15);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn lazy_map_reader_shares_map_reader_surface() {
+        let a = Observable::new(1);
+        let calls = Rc::new(Cell::new(0));
+        let doubled = {
+            let calls = calls.clone();
+            crate::map_obs_lazy!(
+                move |a: &i32| {
+                    calls.set(calls.get() + 1);
+                    a * 2
+                },
+                a
+            )
+        };
+
+        // Listening needs the value, so it is calculated right away
+        let updates = Rc::new(Cell::new(0));
+        let _updated = {
+            let updates = updates.clone();
+            doubled.on_updated(move || updates.set(updates.get() + 1))
+        };
+        assert!(doubled.is_computed());
+        assert_eq!(calls.get(), 1);
+
+        let once: Rc<RefCell<Vec<i32>>> = Rc::default();
+        let _once = {
+            let once = once.clone();
+            doubled.once(move |v| once.borrow_mut().push(*v))
+        };
+        let reader = doubled.reader();
+        a.set(2);
+        a.set(3);
+        assert_eq!(*reader.value(), 6);
+        assert_eq!(*once.borrow(), vec![4]);
+        assert_eq!(updates.get(), 2);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn indexed_by_follows_pushes() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Entity {
+            id: u32,
+            name: &'static str,
+        }
+        let obs: Observable<Vec<Entity>> = Observable::new(vec![]);
+        let by_id = obs.indexed_by(|entity| entity.id);
+
+        obs.push(Entity { id: 7, name: "a" });
+        obs.push(Entity { id: 3, name: "b" });
+        assert_eq!(by_id.value().len(), 2);
+        assert_eq!(by_id.value()[&3].name, "b");
+
+        obs.modify_at(0, |entity| entity.name = "c");
+        assert_eq!(by_id.value()[&7].name, "c");
+    }
+
+    #[test]
+    fn recompute_if_dirty_refreshes_stale_value() {
+        let obs = Observable::new(1);
+        let mapped = Rc::new(Cell::new(0));
+        let doubled = {
+            let mapped = mapped.clone();
+            obs.map_value(move |v| {
+                mapped.set(mapped.get() + 1);
+                v * 2
+            })
+        };
+
+        // Changed without notifying, so the mapper doesn't know yet
+        obs.modify(|v| {
+            *v = 5;
+            false
+        });
+        doubled.mark_dirty();
+        assert!(doubled.is_dirty());
+        assert_eq!(*doubled.value(), 2);
+
+        assert!(doubled.recompute_if_dirty());
+        assert_eq!(*doubled.value(), 10);
+        assert!(!doubled.recompute_if_dirty());
+        assert_eq!(mapped.get(), 2);
+
+        // Notifying recalculates it anyway, which leaves nothing for recompute_if_dirty to do
+        doubled.mark_dirty();
+        obs.set(6);
+        assert!(!doubled.is_dirty());
+        assert!(!doubled.recompute_if_dirty());
+        assert_eq!(*doubled.value(), 12);
+        assert_eq!(mapped.get(), 3);
+    }
+
+    #[test]
+    fn map_value_manual_waits_for_recompute() {
+        let obs = Observable::new(1);
+        let scheduled = Rc::new(Cell::new(0));
+        let mapped = Rc::new(Cell::new(0));
+        let doubled = {
+            let scheduled = scheduled.clone();
+            let mapped = mapped.clone();
+            obs.map_value_manual(
+                move || scheduled.set(scheduled.get() + 1),
+                move |v| {
+                    mapped.set(mapped.get() + 1);
+                    v * 2
+                },
+            )
+        };
+        let notified = Rc::new(Cell::new(0));
+        let _sub = {
+            let notified = notified.clone();
+            doubled.subscribe(move |_| notified.set(notified.get() + 1))
+        };
+        assert_eq!(mapped.get(), 1);
+
+        obs.set(5);
+        obs.set(6);
+        assert!(doubled.is_dirty());
+        assert_eq!(*doubled.value(), 2);
+        assert_eq!((scheduled.get(), mapped.get(), notified.get()), (1, 1, 0));
+
+        assert!(doubled.recompute_if_dirty());
+        assert_eq!(*doubled.value(), 12);
+        assert_eq!((scheduled.get(), mapped.get(), notified.get()), (1, 2, 1));
+        assert!(!doubled.recompute_if_dirty());
+
+        obs.set(7);
+        assert_eq!(scheduled.get(), 2);
+    }
+
+    #[test]
+    fn clamp_keeps_within_bounds() {
+        let obs = Observable::new(0.5);
+        let clamped = obs.clamp(0.0, 1.0);
+        let notified = Rc::new(Cell::new(0));
+        let _sub = {
+            let notified = notified.clone();
+            clamped.subscribe(move |_| notified.set(notified.get() + 1))
+        };
+
+        for (value, expected) in [(2.0, 1.0), (3.0, 1.0), (-1.0, 0.0), (0.25, 0.25)] {
+            obs.set(value);
+            assert_eq!(*clamped.value(), expected);
+        }
+        // 3.0 clamps to the same value as 2.0
+        assert_eq!(notified.get(), 3);
+
+        obs.set(f64::NAN);
+        assert!(clamped.value().is_nan());
+    }
+
+    #[test]
+    fn min_and_max_with_follow_both() {
+        let a = Observable::new(1.0);
+        let b = Observable::new(2.0);
+        let min = a.min_with(b.reader());
+        let max = a.max_with(b.reader());
+        assert_eq!((*min.value(), *max.value()), (1.0, 2.0));
+
+        a.set(3.0);
+        assert_eq!((*min.value(), *max.value()), (2.0, 3.0));
+        b.set(f64::NAN);
+        assert_eq!((*min.value(), *max.value()), (3.0, 3.0));
+    }
+
+    #[test]
+    fn toggle_flips_and_notifies() {
+        let obs = Observable::new(false);
+        let seen: Rc<RefCell<Vec<bool>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            obs.subscribe(move |v| seen.borrow_mut().push(*v))
+        };
+        obs.toggle();
+        obs.toggle();
+        assert_eq!(*seen.borrow(), vec![true, false]);
+    }
+
+    #[test]
+    fn bool_combinator_truth_tables() {
+        let a = Observable::new(false);
+        let b = Observable::new(false);
+        let and = a.and(b.reader());
+        let or = a.or(b.reader());
+        let not = a.not();
+        let not_b = !b.reader();
+        let not_a = !&a;
+        let and_notified = Rc::new(Cell::new(0));
+        let _sub = {
+            let and_notified = and_notified.clone();
+            and.subscribe(move |_| and_notified.set(and_notified.get() + 1))
+        };
+
+        for (va, vb) in [(false, false), (true, false), (true, true), (false, true)] {
+            a.set(va);
+            b.set(vb);
+            assert_eq!(*and.value(), va && vb);
+            assert_eq!(*or.value(), va || vb);
+            assert_eq!(*not.value(), !va);
+            assert_eq!(*not_a.value(), !va);
+            assert_eq!(*not_b.value(), !vb);
+        }
+        // Only true && true, and back again
+        assert_eq!(and_notified.get(), 2);
+    }
+
+    #[test]
+    fn extend_notifies_once() {
+        let obs: Observable<Vec<i32>> = Observable::new(vec![]);
+        let notified = Rc::new(Cell::new(0));
+        let _sub = {
+            let notified = notified.clone();
+            obs.subscribe(move |_| notified.set(notified.get() + 1))
+        };
+
+        obs.extend(1..=5);
+        assert_eq!(notified.get(), 1);
+        assert_eq!(obs.value().len(), 5);
+
+        obs.extend(std::iter::empty());
+        assert_eq!(notified.get(), 1);
+    }
+
+    #[test]
+    fn modify_notifies_only_when_asked() {
+        let obs = Observable::new(vec![1]);
+        let seen: Rc<RefCell<Vec<Vec<i32>>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            obs.subscribe(move |v| seen.borrow_mut().push(v.clone()))
+        };
+
+        assert!(!obs.modify(|v| {
+            v.push(2);
+            false
+        }));
+        assert!(seen.borrow().is_empty());
+        assert_eq!(*obs.value(), vec![1, 2]);
+
+        assert!(obs.modify(|v| {
+            v.push(3);
+            true
+        }));
+        assert_eq!(*seen.borrow(), vec![vec![1, 2, 3]]);
+    }
+
+    #[test]
+    fn listener_metrics_count_pruned_listeners() {
+        let obs = Observable::new(1);
+        let _kept = obs.subscribe(|_| {});
+        let dropped: Vec<Subscription> = (0..3).map(|_| obs.subscribe(|_| {})).collect();
+        let once = obs.once(|_| {});
+        drop(dropped);
+        drop(once);
+
+        let metrics = obs.listener_metrics();
+        assert_eq!(
+            (metrics.subscribed, metrics.live, metrics.pruned),
+            (5, 1, 0)
+        );
+
+        obs.set(2);
+        let metrics = obs.listener_metrics();
+        assert_eq!(
+            (metrics.subscribed, metrics.live, metrics.pruned),
+            (5, 1, 4)
+        );
+    }
 
-        $(let $obs = {
-            let reader: Reader<_> = $obs.reader();
-            let (value, listener_set) = reader.split();
-            listener_set_list.push(listener_set);
-            value
-        };)+
-        let listener_set: UniqueRef<ListenerSet> = UniqueRef::default();
-        #[allow(clippy::redundant_closure_call)]
-        let calc = move || $cb($(&*$obs.get(),)*);
-        let value = calc();
-        let value = Value::rc(value);
+    #[test]
+    fn listener_count_drops_with_subscription() {
+        let obs = Observable::new(1);
+        let reader = obs.reader();
+        let sub = obs.subscribe(|_| {});
+        let _once = obs.once(|_| {});
+        assert_eq!(obs.listener_count(), 2);
 
-        let closure: Rc<dyn Dispatch> = {
-            let listener_set = listener_set.downgrade();
-            let value = Rc::downgrade(&value);
+        obs.set(2);
+        assert_eq!(reader.listener_count(), Some(1));
+        drop(sub);
+        obs.force_notify();
+        assert_eq!(obs.listener_count(), 0);
 
-            Rc::new(move || {
-                let reader_value = value.upgrade()?;
-                let listener_set = listener_set.upgrade()?;
+        drop(obs);
+        assert_eq!(reader.listener_count(), None);
+    }
 
-                reader_value.set(calc());
-                listener_set.notify();
-                Some(())
+    #[test]
+    fn reentrant_set_is_deferred() {
+        let obs = Observable::new(0);
+        let seen: Rc<RefCell<Vec<(&str, i32)>>> = Rc::default();
+        let _counter = {
+            let weak = obs.downgrade();
+            let seen = seen.clone();
+            obs.on_updated(move || {
+                let value = weak.reader().unwrap().value_cloned();
+                seen.borrow_mut().push(("counter", value));
+                if value < 3 {
+                    weak.set(value + 1);
+                }
             })
         };
-        let weak_closure = Rc::downgrade(&closure);
-        for ls in listener_set_list.into_iter() {
-            if let Some(listener_set) = ls.upgrade() {
-                let closure = weak_closure.clone();
-                listener_set.subscribe_weak(closure);
-            }
-        }
-        let closure: Rc<dyn Dispatch> = closure.clone();
+        let _watcher = {
+            let seen = seen.clone();
+            obs.subscribe(move |v| seen.borrow_mut().push(("watcher", *v)))
+        };
 
-        MapReader::from((value, listener_set, closure))
-    }};
-}
+        obs.set(1);
+        // Every listener sees each round through, rather than the counter recursing ahead of the watcher
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                ("counter", 1),
+                ("watcher", 2),
+                ("counter", 2),
+                ("watcher", 3),
+                ("counter", 3),
+                ("watcher", 3),
+            ]
+        );
+    }
 
-pub struct DynMapReaderContext<'a> {
-    index: usize,
-    dyn_downstreams: &'a Downstreams,
-    initilized: bool,
-    closure: Weak<dyn Dispatch>,
-}
-type Downstreams = RefCell<Vec<(*const (), Option<Subscription>)>>;
+    #[test]
+    fn on_active_change_follows_first_and_last_subscriber() {
+        let obs = Observable::new(1);
+        let changes: Rc<RefCell<Vec<bool>>> = Rc::default();
+        let _active = {
+            let changes = changes.clone();
+            obs.on_active_change(move |active| changes.borrow_mut().push(active))
+        };
 
-impl<'ctx> DynMapReaderContext<'ctx> {
-    fn track_dyn_reader(&mut self, value_ptr: *const (), listener_set: &WeakRef<ListenerSet>) {
-        let index = self.index;
-        let mut list = self.dyn_downstreams.borrow_mut();
+        let a = obs.subscribe(|_| {});
+        let b = obs.once(|_| {});
+        assert_eq!(*changes.borrow(), vec![true]);
 
-        if index < list.len() {
-            if value_ptr != list[index].0 {
-                let cb = self.subscription_closure();
-                let sub = listener_set.upgrade().map(|ls| ls.subscribe(cb));
-                list[index] = (value_ptr, sub);
-            }
-        } else {
-            let cb = self.subscription_closure();
-            let sub = listener_set.upgrade().map(|ls| ls.subscribe(cb));
-            list.push((value_ptr, sub))
-        }
-        self.index += 1;
+        drop(a);
+        assert_eq!(*changes.borrow(), vec![true]);
+        drop(b);
+        assert_eq!(*changes.borrow(), vec![true, false]);
+
+        let _c = obs.subscribe(|_| {});
+        assert_eq!(*changes.borrow(), vec![true, false, true]);
     }
-    #[inline]
-    pub fn track_dyn<'a, V>(&mut self, reader: &'a Reader<V>) -> Ref<'a, V> {
-        let value_ptr = Rc::as_ptr(&reader.value) as *const ();
-        self.track_dyn_reader(value_ptr, &reader.listener_set);
-        reader.value.get()
+
+    #[test]
+    fn batch_notifies_once() {
+        let obs: Observable<Vec<u32>> = Observable::default();
+        let seen: Rc<RefCell<Vec<usize>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            obs.subscribe(move |v| seen.borrow_mut().push(v.len()))
+        };
+
+        obs.batch(|obs| {
+            obs.push(1);
+            obs.push(2);
+            obs.batch(|obs| obs.push(3));
+            assert!(seen.borrow().is_empty());
+        });
+        assert_eq!(*seen.borrow(), vec![3]);
+
+        // A batch without changes doesn't notify
+        obs.batch(|_| {});
+        assert_eq!(seen.borrow().len(), 1);
     }
 
-    pub fn track_reader(&self, listener_set: &WeakRef<ListenerSet>) {
-        if !self.initilized {
-            if let Some(ls) = listener_set.upgrade() {
-                ls.subscribe_weak(self.closure.clone())
-            }
+    #[test]
+    fn transaction_commit_and_rollback() {
+        let obs = Observable::new(1);
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            obs.subscribe(move |v| seen.borrow_mut().push(*v))
+        };
+
+        let tx = obs.transaction();
+        obs.set(2);
+        obs.set(3);
+        assert!(seen.borrow().is_empty());
+        tx.commit();
+        assert_eq!(*obs.value(), 3);
+        assert_eq!(*seen.borrow(), vec![3]);
+
+        let tx = obs.transaction();
+        obs.set(4);
+        assert_eq!(*obs.value(), 4);
+        tx.rollback();
+        assert_eq!(*obs.value(), 3);
+
+        // Dropping without committing rolls back too
+        {
+            let _tx = obs.transaction();
+            obs.set(5);
         }
+        assert_eq!(*obs.value(), 3);
+        assert_eq!(*seen.borrow(), vec![3]);
+
+        // Listeners are back to normal afterwards
+        obs.set(6);
+        assert_eq!(*seen.borrow(), vec![3, 6]);
     }
-    #[inline]
-    pub fn track<'a, V>(&self, reader: &'a Reader<V>) -> Ref<'a, V> {
-        self.track_reader(&reader.listener_set);
-        reader.value.get()
+
+    #[test]
+    fn hydration_defers_subscribers() {
+        let obs = Observable::new(String::from("server"));
+        let seen: Rc<RefCell<Vec<String>>> = Rc::default();
+
+        obs.begin_hydration();
+        assert!(obs.is_hydrating());
+        let _sub = {
+            let seen = seen.clone();
+            obs.subscribe(move |v| seen.borrow_mut().push(v.clone()))
+        };
+        let _once = {
+            let seen = seen.clone();
+            obs.once(move |v| seen.borrow_mut().push(format!("once {}", v)))
+        };
+        obs.set("partial".into());
+        assert!(seen.borrow().is_empty());
+
+        obs.end_hydration("hydrated".into());
+        assert!(!obs.is_hydrating());
+        assert_eq!(*seen.borrow(), vec!["hydrated", "once hydrated"]);
     }
-}
 
-impl<'ctx> DynMapReaderContext<'ctx> {
-    fn clear_unused_readers(&self) {
-        let mut list = self.dyn_downstreams.borrow_mut();
-        let new_len = self.index;
-        list.truncate(new_len);
+    #[test]
+    fn last_change_location() {
+        let obs = Observable::new(vec![]);
+        assert!(obs.last_change_location().is_none());
+
+        let line = line!() + 1;
+        obs.set(vec![1]);
+        let location = obs.last_change_location().unwrap();
+        assert_eq!((location.file(), location.line()), (file!(), line));
+
+        let line = line!() + 1;
+        obs.push(2);
+        assert_eq!(obs.last_change_location().unwrap().line(), line);
     }
-}
-impl<'ctx> Drop for DynMapReaderContext<'ctx> {
-    fn drop(&mut self) {
-        self.clear_unused_readers();
+
+    #[test]
+    fn sorted_by_view() {
+        let obs: Observable<Vec<(u32, &str)>> = Observable::default();
+        let by_rank = obs.sorted_by(|(rank, _)| *rank);
+
+        obs.push((3, "c"));
+        obs.push((1, "a"));
+        obs.push((2, "b"));
+        obs.push((1, "also a"));
+
+        assert_eq!(
+            *by_rank.value(),
+            vec![(1, "a"), (1, "also a"), (2, "b"), (3, "c")]
+        );
+        assert_eq!(obs.value()[0], (3, "c"));
     }
-}
-impl<'ctx> DynMapReaderContext<'ctx> {
-    fn subscription_closure(&self) -> impl Dispatch + 'static {
-        let cb = self.closure.clone();
-        move || {
-            if let Some(f) = cb.upgrade() {
-                f.dispatch();
-            }
+
+    #[test]
+    fn subscribe_with_replay() {
+        let history = Observable::new(RingBuffer::new(5));
+        for i in 1..=4 {
+            history.push(i);
         }
+
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            history.subscribe_with_replay(2, move |v| seen.borrow_mut().push(*v))
+        };
+        assert_eq!(*seen.borrow(), vec![3, 4]);
+
+        history.push(5);
+        assert_eq!(*seen.borrow(), vec![3, 4, 5]);
     }
-}
 
-#[cfg(test)]
-mod test {
-    use std::{
-        cell::{Cell, RefCell},
-        rc::Rc,
-    };
+    #[test]
+    fn subscribe_with_replay_delivers_each_item_once() {
+        let history = Observable::new(RingBuffer::new(5));
+        history.push(1);
+
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            history.subscribe_with_replay(5, move |v| seen.borrow_mut().push(*v))
+        };
+        assert_eq!(*seen.borrow(), vec![1]);
 
-    use crate::{Pushable, Reader, Subscription};
+        history.extend([2, 3, 4]);
+        assert_eq!(*seen.borrow(), vec![1, 2, 3, 4]);
 
-    use super::Observable;
+        history.force_notify();
+        history.modify(|_| true);
+        assert_eq!(*seen.borrow(), vec![1, 2, 3, 4]);
+
+        history.push(5);
+        assert_eq!(*seen.borrow(), vec![1, 2, 3, 4, 5]);
+    }
 
     #[test]
-    fn observable_vec_push() {
-        let obs = Observable::new(vec![1, 2, 3]);
+    fn observable_set_push_ignores_duplicates() {
+        let obs: Observable<std::collections::BTreeSet<u32>> = Observable::default();
+        let calls = Rc::new(Cell::new(0));
+        let _sub = {
+            let calls = calls.clone();
+            obs.subscribe(move |_| calls.set(calls.get() + 1))
+        };
 
-        let counter: Rc<RefCell<Option<usize>>> = Rc::new(RefCell::new(None));
+        obs.push(1);
+        obs.push(2);
+        assert_eq!(calls.get(), 2);
+        obs.push(1);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(obs.value().len(), 2);
+    }
+
+    #[test]
+    fn observable_try_push() {
+        let obs = Observable::new(RingBuffer::new(2));
 
+        let counter: Rc<Cell<usize>> = Rc::default();
         let _sub = {
             let counter = counter.clone();
-            obs.subscribe(move |v: &Vec<u32>| {
-                counter.replace(Some(v.len()));
-            })
+            obs.subscribe(move |_| counter.set(counter.get() + 1))
         };
 
-        assert_eq!(*counter.borrow(), None);
-        obs.push(0);
-        assert_eq!(*counter.borrow(), Some(4));
+        assert_eq!(obs.try_push(1), Ok(()));
+        assert_eq!(obs.try_push(2), Ok(()));
+        assert_eq!(counter.get(), 2);
+
+        assert_eq!(obs.try_push(3), Err(3));
+        assert_eq!(counter.get(), 2);
+        assert_eq!(obs.value().len(), 2);
     }
 
     struct Wrapper<T>(Vec<T>);
@@ -546,6 +2736,232 @@ mod test {
         assert_eq!(*counter_once.borrow(), Some(1));
     }
 
+    #[test]
+    fn paused_subscription() {
+        let obs = Observable::new(0);
+
+        let counter: Rc<Cell<usize>> = Rc::default();
+        let sub = {
+            let counter = counter.clone();
+            obs.subscribe(move |_| counter.set(counter.get() + 1))
+        };
+
+        sub.pause();
+        obs.set(1);
+        assert_eq!(counter.get(), 0);
+
+        sub.resume();
+        obs.set(2);
+        assert_eq!(counter.get(), 1);
+    }
+
+    #[test]
+    fn subscribe_on_delta() {
+        let obs = Observable::new(0.0);
+
+        let fired: Rc<RefCell<Vec<f64>>> = Rc::default();
+        let _sub = {
+            let fired = fired.clone();
+            obs.subscribe_on_delta(1.0, move |v| fired.borrow_mut().push(*v))
+        };
+
+        obs.set(0.5);
+        obs.set(0.9);
+        obs.set(1.5);
+        obs.set(2.0);
+        obs.set(0.0);
+
+        assert_eq!(*fired.borrow(), vec![1.5, 0.0]);
+    }
+
+    #[test]
+    fn weak_observable_in_subscriber() {
+        let a = Observable::new(0);
+        let b = Observable::shared(0);
+
+        let _sub = {
+            let b = b.downgrade();
+            a.subscribe(move |a| {
+                b.set(*a * 2);
+            })
+        };
+
+        a.set(2);
+        assert_eq!(*b.value(), 4);
+
+        let weak_b = b.downgrade();
+        drop(b);
+        assert!(weak_b.reader().is_none());
+        assert!(!weak_b.set(1));
+
+        // The subscriber's handle is dead, which is quietly ignored
+        a.set(3);
+    }
+
+    #[test]
+    fn effect_runs_until_dropped() {
+        let obs = Observable::new(1);
+
+        let runs: Rc<RefCell<Vec<i32>>> = Rc::default();
+        let handle = {
+            let runs = runs.clone();
+            obs.reader().effect(move |v| runs.borrow_mut().push(*v))
+        };
+        assert_eq!(*runs.borrow(), vec![1]);
+
+        obs.set(2);
+        obs.set(3);
+        assert_eq!(*runs.borrow(), vec![1, 2, 3]);
+
+        drop(handle);
+        obs.set(4);
+        assert_eq!(*runs.borrow(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn sample_every_nth_change() {
+        let obs = Observable::new(0);
+
+        let fired: Rc<RefCell<Vec<i32>>> = Rc::default();
+        let _sub = {
+            let fired = fired.clone();
+            obs.reader()
+                .sample_every(3, move |v| fired.borrow_mut().push(*v))
+                .unwrap()
+        };
+        for i in 1..=10 {
+            obs.set(i);
+        }
+        assert_eq!(*fired.borrow(), vec![3, 6, 9]);
+    }
+
+    #[test]
+    fn map_entries_only_recomputes_changed() {
+        let obs: Observable<HashMap<&str, u32>> =
+            Observable::new(HashMap::from([("a", 1), ("b", 2)]));
+
+        let calls: Rc<RefCell<Vec<&str>>> = Rc::default();
+        let doubled = {
+            let calls = calls.clone();
+            obs.map_entries(8, move |k, v| {
+                calls.borrow_mut().push(k);
+                v * 2
+            })
+        };
+        calls.borrow_mut().sort();
+        assert_eq!(*calls.borrow(), vec!["a", "b"]);
+        calls.borrow_mut().clear();
+
+        obs.set(HashMap::from([("a", 1), ("b", 5)]));
+        assert_eq!(*calls.borrow(), vec!["b"]);
+        assert_eq!(doubled.value()["a"], 2);
+        assert_eq!(doubled.value()["b"], 10);
+
+        calls.borrow_mut().clear();
+        obs.set(HashMap::from([("b", 5)]));
+        assert!(calls.borrow().is_empty());
+        assert!(!doubled.value().contains_key("a"));
+    }
+
+    #[test]
+    fn map_entries_evicts_least_recently_used() {
+        let obs: Observable<HashMap<&str, u32>> =
+            Observable::new(HashMap::from([("a", 1), ("b", 2)]));
+
+        let calls: Rc<Cell<usize>> = Rc::default();
+        let doubled = {
+            let calls = calls.clone();
+            obs.map_entries(1, move |_, v| {
+                calls.set(calls.get() + 1);
+                v * 2
+            })
+        };
+        assert_eq!(calls.get(), 2);
+
+        // Only one of the two results fits in the cache, so the other is recomputed
+        obs.set(HashMap::from([("a", 1), ("b", 2)]));
+        assert_eq!(calls.get(), 3);
+        assert_eq!(*doubled.value(), HashMap::from([("a", 2), ("b", 4)]));
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn notify_logs_at_debug() {
+        use std::sync::Mutex;
+
+        static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+        struct Capture;
+        impl log::Log for Capture {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                let entry = (record.level(), record.args().to_string());
+                RECORDS.lock().unwrap().push(entry);
+            }
+            fn flush(&self) {}
+        }
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        struct Probe;
+        let obs = Observable::new(Probe);
+        let sub = obs.subscribe(|_| {});
+        obs.force_notify();
+        drop(sub);
+
+        let records = RECORDS.lock().unwrap();
+        let probe: Vec<&(log::Level, String)> = records
+            .iter()
+            .filter(|(_, msg)| msg.contains("::Probe"))
+            .collect();
+        assert!(probe.iter().all(|(level, _)| *level == log::Level::Debug));
+        assert!(probe.iter().any(|(_, msg)| msg.starts_with("subscribe to")));
+        assert!(probe
+            .iter()
+            .any(|(_, msg)| msg.starts_with("notify") && msg.ends_with("(1 listeners)")));
+        assert!(probe
+            .iter()
+            .any(|(_, msg)| msg.starts_with("unsubscribe from")));
+    }
+
+    #[test]
+    fn set_and_count() {
+        let obs = Observable::new(0);
+        assert_eq!(obs.set_and_count(1), 0);
+
+        let a = obs.subscribe(|_| {});
+        let b = obs.subscribe(|_| {});
+        let _once = obs.once(|_| {});
+        assert_eq!(obs.set_and_count(2), 3);
+        assert_eq!(obs.set_and_count(3), 2);
+
+        b.pause();
+        assert_eq!(obs.set_and_count(4), 1);
+        drop(a);
+        assert_eq!(obs.set_and_count(5), 0);
+    }
+
+    #[test]
+    fn complete_on_drop() {
+        let obs = Observable::new(1);
+        let reader = obs.reader();
+
+        let completed: Rc<Cell<usize>> = Rc::default();
+        let _sub = {
+            let completed = completed.clone();
+            reader.on_complete(move || completed.set(completed.get() + 1))
+        };
+
+        obs.set(2);
+        assert_eq!(completed.get(), 0);
+
+        drop(obs);
+        assert_eq!(completed.get(), 1);
+        assert_eq!(*reader.value(), 2);
+        assert!(reader.on_complete(|| {}).is_none());
+    }
+
     #[test]
     fn observable_map() {
         let obs1 = Observable::new(0);
@@ -562,6 +2978,78 @@ mod test {
             assert_eq!(*map_reader.value(), 3);
         }
     }
+    #[test]
+    fn try_map_value_errors() {
+        let obs = Observable::new(String::from("1"));
+        let parsed = obs.try_map_value(|s: &String| s.parse::<u32>().map_err(|e| e.to_string()));
+        let errors = parsed.errors();
+        let seen: Rc<RefCell<Vec<Option<String>>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            errors.subscribe(move |e| seen.borrow_mut().push(e.clone()))
+        };
+        assert_eq!(*parsed.value(), Ok(1));
+        assert_eq!(*errors.value(), None);
+
+        obs.set("one".into());
+        assert!(parsed.value().is_err());
+        assert_eq!(
+            *errors.value(),
+            Some("invalid digit found in string".to_string())
+        );
+
+        obs.set("2".into());
+        assert_eq!(*parsed.value(), Ok(2));
+        assert_eq!(*errors.value(), None);
+        assert_eq!(seen.borrow().len(), 2);
+    }
+    #[test]
+    fn map_value_distinct_parity() {
+        let obs = Observable::new(1u32);
+        let parity = obs.map_value_distinct(|n| n % 2);
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            parity.subscribe(move |p| seen.borrow_mut().push(*p))
+        };
+
+        for n in [3, 5, 6, 8, 9] {
+            obs.set(n);
+        }
+        assert_eq!(*seen.borrow(), vec![0, 1]);
+        assert_eq!(*parity.value(), 1);
+    }
+    #[test]
+    fn combine_latest_sums() {
+        let inputs: Vec<Observable<u32>> = (1..=3).map(Observable::new).collect();
+        let sum =
+            crate::combine_latest(inputs.iter().map(|obs| obs.reader()).collect(), |values| {
+                values.iter().sum::<u32>()
+            });
+        assert_eq!(*sum.value(), 6);
+
+        inputs[0].set(10);
+        assert_eq!(*sum.value(), 15);
+        inputs[2].set(30);
+        assert_eq!(*sum.value(), 42);
+    }
+    #[test]
+    fn observable_map_weak() {
+        let obs1 = Observable::new(1);
+        let map_reader = obs1.map_value_weak(|n| n * 10);
+        assert_eq!(*map_reader.value(), 10);
+
+        obs1.set(2);
+        assert_eq!(*map_reader.value(), 20);
+
+        let source = Rc::downgrade(&obs1.value);
+        drop(obs1);
+
+        // The mapper did not pin the source, and keeps its last value
+        assert!(source.upgrade().is_none());
+        assert_eq!(*map_reader.value(), 20);
+    }
+
     struct Dog {
         weight_kg: Observable<f32>,
     }
@@ -662,6 +3150,41 @@ mod test {
         };
         assert_eq!(*dog_mapped_reader.value(), 11.1);
     }
+
+    #[test]
+    fn map_reader_resubscribes_on_listener_set_swap() {
+        let a = Observable::new(1);
+        let b = Observable::new(0);
+
+        // Same value as `a`, but notified through `b`'s listener set
+        let a_via_b = Reader {
+            value: a.reader().value,
+            listener_set: b.listener_set.downgrade(),
+        };
+
+        let use_b = Observable::new(false);
+        let mapped = {
+            let a = a.reader();
+            use_b.reader().map_reader(
+                move |use_b| {
+                    if *use_b {
+                        a_via_b.clone()
+                    } else {
+                        a.clone()
+                    }
+                },
+            )
+        };
+        assert_eq!(*mapped.value(), 1);
+
+        // The value pointer is unchanged, only the listener set differs
+        use_b.set(true);
+        assert_eq!(*mapped.value(), 1);
+
+        a.value.set(2);
+        b.force_notify();
+        assert_eq!(*mapped.value(), 2);
+    }
 }
 
 #[cfg(test)]
@@ -670,6 +3193,7 @@ mod view_mode_example {
 
     use crate::{MapReader, Observable, Reader};
 
+    #[allow(dead_code)]
     trait ViewModel {
         type Parent: ViewModel;
         fn parent(&self) -> Weak<Self::Parent>;
@@ -710,6 +3234,7 @@ mod view_mode_example {
     }
 
     struct Member {
+        #[allow(dead_code)]
         parent: Weak<TopicSpace>,
         override_clip_box: Observable<Option<OverrideBoundingBox>>,
         clip_box: MapReader<BoundingBox>,
@@ -1,6 +1,26 @@
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+
 pub trait Pushable {
     type Value;
     fn push(&mut self, value: Self::Value);
+    /// Push, returning whether the collection changed. Collections which may ignore a pushed
+    /// value, like sets, override this so that observers are only notified of actual changes
+    fn push_changed(&mut self, value: Self::Value) -> bool {
+        self.push(value);
+        true
+    }
+    /// Push each of the values, returning whether the collection changed
+    fn push_all(&mut self, values: impl IntoIterator<Item = Self::Value>) -> bool
+    where
+        Self: Sized,
+    {
+        let mut changed = false;
+        for value in values {
+            changed |= self.push_changed(value);
+        }
+        changed
+    }
 }
 
 impl<T> Pushable for Vec<T> {
@@ -9,3 +29,28 @@ impl<T> Pushable for Vec<T> {
         self.push(value)
     }
 }
+
+impl<T: Eq + Hash> Pushable for HashSet<T> {
+    type Value = T;
+    fn push(&mut self, value: Self::Value) {
+        self.insert(value);
+    }
+    fn push_changed(&mut self, value: Self::Value) -> bool {
+        self.insert(value)
+    }
+}
+
+impl<T: Ord> Pushable for BTreeSet<T> {
+    type Value = T;
+    fn push(&mut self, value: Self::Value) {
+        self.insert(value);
+    }
+    fn push_changed(&mut self, value: Self::Value) -> bool {
+        self.insert(value)
+    }
+}
+
+/// A collection which may reject a pushed value, for instance because it is at capacity
+pub trait TryPushable: Pushable {
+    fn try_push(&mut self, value: Self::Value) -> Result<(), Self::Value>;
+}
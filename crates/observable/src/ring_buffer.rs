@@ -0,0 +1,117 @@
+use std::collections::VecDeque;
+
+use crate::{Pushable, TryPushable};
+
+/// A fixed capacity buffer. `push` evicts the oldest item when full, whereas `try_push` refuses
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    pushed: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        RingBuffer {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            pushed: 0,
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.capacity
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+    /// How many items have ever been stored, including those since evicted
+    pub fn total_pushed(&self) -> usize {
+        self.pushed
+    }
+    /// The most recently pushed item
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+}
+
+impl<T> Pushable for RingBuffer<T> {
+    type Value = T;
+    fn push(&mut self, value: Self::Value) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.is_full() {
+            self.items.pop_front();
+        }
+        self.items.push_back(value);
+        self.pushed += 1;
+    }
+    /// A zero capacity buffer stores nothing, so pushing into it is not a change
+    fn push_changed(&mut self, value: Self::Value) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        self.push(value);
+        true
+    }
+}
+
+impl<T> TryPushable for RingBuffer<T> {
+    fn try_push(&mut self, value: Self::Value) -> Result<(), Self::Value> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.items.push_back(value);
+        self.pushed += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{Observable, Pushable, RingBuffer, TryPushable};
+
+    #[test]
+    fn push_evicts_oldest() {
+        let mut buf = RingBuffer::new(2);
+        buf.push(1);
+        buf.push(2);
+        buf.push(3);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn try_push_rejects_when_full() {
+        let mut buf = RingBuffer::new(2);
+        assert_eq!(buf.try_push(1), Ok(()));
+        assert_eq!(buf.try_push(2), Ok(()));
+        assert_eq!(buf.try_push(3), Err(3));
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn zero_capacity_does_not_notify() {
+        let obs = Observable::new(RingBuffer::new(0));
+        let calls: Rc<Cell<usize>> = Rc::default();
+        let _sub = {
+            let calls = calls.clone();
+            obs.subscribe(move |_| calls.set(calls.get() + 1))
+        };
+
+        obs.push(1);
+        obs.extend([2, 3]);
+        assert_eq!(calls.get(), 0);
+        assert!(obs.value().is_empty());
+    }
+}
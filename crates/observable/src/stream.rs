@@ -0,0 +1,125 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::{Stream, StreamExt};
+
+use crate::{spawn_local, Observable, Reader, Subscription};
+
+/// The values of a Reader as a Stream, see Reader::into_stream
+pub struct ReaderStream<T> {
+    rx: UnboundedReceiver<T>,
+    // Dropped along with the stream
+    _subs: (Option<Subscription>, Option<Subscription>),
+}
+
+impl<T> Stream for ReaderStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        Pin::new(&mut self.rx).poll_next(cx)
+    }
+}
+
+impl<T: Clone + 'static> Reader<T> {
+    /// A Stream yielding the value on every change, for async consumers. Values queue up until they
+    /// are polled. The stream ends once the Observable is dropped, and dropping the stream unsubscribes
+    pub fn into_stream(self) -> ReaderStream<T> {
+        let (tx, mut rx) = unbounded();
+        let on_change = {
+            let tx = tx.clone();
+            self.subscribe(move |value: &T| {
+                let _ = tx.unbounded_send(value.clone());
+            })
+        };
+        let on_complete = self.on_complete(move || tx.close_channel());
+        if on_complete.is_none() {
+            // Already gone, so there will be no values
+            rx.close();
+        }
+        ReaderStream {
+            rx,
+            _subs: (on_change, on_complete),
+        }
+    }
+}
+
+impl<T: 'static> Observable<T> {
+    /// An Observable which is set to each item of `stream`, starting out as `initial`. The stream is
+    /// pumped by a task spawned with spawn_local. That task only holds a weak handle to the Observable,
+    /// so dropping the Observable ends it, although not until the stream yields its next item
+    pub fn from_stream(initial: T, stream: impl Stream<Item = T> + 'static) -> Observable<T> {
+        let obs = Observable::new(initial);
+        let weak = obs.downgrade();
+        spawn_local(async move {
+            let mut stream = Box::pin(stream);
+            while let Some(item) = stream.next().await {
+                if !weak.set(item) {
+                    break;
+                }
+            }
+        });
+        obs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[test]
+    fn stream_of_changes() {
+        let obs = Observable::new(1);
+        let stream = obs.reader().into_stream();
+
+        obs.set(2);
+        obs.set(3);
+        drop(obs);
+
+        let values: Vec<u32> = block_on(stream.collect());
+        assert_eq!(values, vec![2, 3]);
+    }
+
+    #[test]
+    fn dropping_stream_unsubscribes() {
+        let obs = Observable::new(1);
+        let stream = obs.reader().into_stream();
+        drop(stream);
+        assert_eq!(obs.set_and_count(2), 0);
+    }
+
+    // On wasm, the pump runs on the browser's event loop instead
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn observable_from_stream() {
+        use std::{cell::RefCell, rc::Rc};
+
+        use futures::{channel::mpsc::unbounded, executor::LocalPool, task::LocalSpawnExt};
+
+        let mut pool = LocalPool::new();
+        let spawner = pool.spawner();
+        crate::set_executor(move |future| spawner.spawn_local(future).unwrap());
+
+        let (tx, rx) = unbounded();
+        let obs = Observable::from_stream(0, rx);
+        let seen: Rc<RefCell<Vec<u32>>> = Rc::default();
+        let _sub = {
+            let seen = seen.clone();
+            obs.subscribe(move |v| seen.borrow_mut().push(*v))
+        };
+
+        tx.unbounded_send(1).unwrap();
+        tx.unbounded_send(2).unwrap();
+        pool.run_until_stalled();
+        assert_eq!(*obs.value(), 2);
+        assert_eq!(*seen.borrow(), vec![1, 2]);
+
+        // Once the Observable is gone, the next item ends the pump, which drops the receiver
+        drop(obs);
+        tx.unbounded_send(3).unwrap();
+        pool.run_until_stalled();
+        assert!(tx.is_closed());
+    }
+}
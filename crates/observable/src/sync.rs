@@ -0,0 +1,284 @@
+use std::{
+    any::Any,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, Weak},
+};
+
+type Listener = dyn Fn() + Send + Sync;
+type Listeners = Mutex<Vec<Weak<Listener>>>;
+
+/// A thread safe counterpart to Observable, for sharing a value with worker threads or tasks on a
+/// multi threaded runtime. Listeners are called on whichever thread sets the value, after its locks
+/// have been released, so they may set it again
+pub struct SyncObservable<T> {
+    value: Arc<RwLock<T>>,
+    listeners: Arc<Listeners>,
+}
+
+/// The thread safe counterpart to Reader. It does not keep the SyncObservable alive, but keeps
+/// its last value once it is dropped
+pub struct SyncReader<T> {
+    value: Arc<RwLock<T>>,
+    listeners: Weak<Listeners>,
+    /// The mapped SyncObservable and its subscription to the source, for readers made by map
+    mapping: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+/// Unsubscribes from a SyncObservable when dropped
+pub struct SyncSubscription {
+    _cb: Arc<Listener>,
+}
+
+impl<T> SyncObservable<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value: Arc::new(RwLock::new(value)),
+            listeners: Arc::default(),
+        }
+    }
+    /// For sharing the SyncObservable between threads
+    pub fn shared(value: T) -> Arc<Self> {
+        Arc::new(Self::new(value))
+    }
+    pub fn reader(&self) -> SyncReader<T> {
+        SyncReader {
+            value: self.value.clone(),
+            listeners: Arc::downgrade(&self.listeners),
+            mapping: None,
+        }
+    }
+    pub fn set(&self, value: T) {
+        *self.value.write().unwrap_or_else(PoisonError::into_inner) = value;
+        self.notify();
+    }
+    /// Modify the value in place and notify. Nobody else can set it in the meantime
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.value.write().unwrap_or_else(PoisonError::into_inner));
+        self.notify();
+    }
+    /// Read the current value. Setting it on the same thread while the guard is held deadlocks
+    pub fn value(&self) -> RwLockReadGuard<'_, T> {
+        read(&self.value)
+    }
+    pub fn value_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.value().clone()
+    }
+    pub fn on_updated(&self, cb: impl Fn() + Send + Sync + 'static) -> SyncSubscription {
+        on_updated(&self.listeners, cb)
+    }
+    /// Call all of the listeners which are still subscribed, returning how many were called
+    pub fn notify(&self) -> usize {
+        notify(&self.listeners)
+    }
+}
+
+impl<T: Send + Sync + 'static> SyncObservable<T> {
+    pub fn subscribe(&self, cb: impl Fn(&T) + Send + Sync + 'static) -> SyncSubscription {
+        self.reader().subscribe(cb).unwrap()
+    }
+    pub fn once(&self, cb: impl FnOnce(&T) + Send + 'static) -> SyncSubscription {
+        self.reader().once(cb).unwrap()
+    }
+    pub fn map<R: Send + Sync + 'static>(
+        &self,
+        f: impl Fn(&T) -> R + Send + Sync + 'static,
+    ) -> SyncReader<R> {
+        self.reader().map(f)
+    }
+}
+
+impl<T> SyncReader<T> {
+    /// Read the current value. Setting it on the same thread while the guard is held deadlocks
+    pub fn value(&self) -> RwLockReadGuard<'_, T> {
+        read(&self.value)
+    }
+    pub fn value_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.value().clone()
+    }
+    /// None if the SyncObservable is gone
+    pub fn on_updated(&self, cb: impl Fn() + Send + Sync + 'static) -> Option<SyncSubscription> {
+        let listeners = self.listeners.upgrade()?;
+        Some(on_updated(&listeners, cb))
+    }
+}
+
+impl<T: Send + Sync + 'static> SyncReader<T> {
+    pub fn subscribe(&self, cb: impl Fn(&T) + Send + Sync + 'static) -> Option<SyncSubscription> {
+        let value = Arc::downgrade(&self.value);
+        self.on_updated(move || {
+            if let Some(value) = value.upgrade() {
+                cb(&read(&value))
+            }
+        })
+    }
+    /// Call `cb` with the next value only
+    pub fn once(&self, cb: impl FnOnce(&T) + Send + 'static) -> Option<SyncSubscription> {
+        let cb = Mutex::new(Some(cb));
+        self.subscribe(move |value| {
+            let cb = cb.lock().unwrap_or_else(PoisonError::into_inner).take();
+            if let Some(cb) = cb {
+                cb(value)
+            }
+        })
+    }
+    /// A reader of `f` applied to the value, recomputed whenever it is set. The mapping lives for as
+    /// long as the returned reader, or any of its clones
+    pub fn map<R: Send + Sync + 'static>(
+        &self,
+        f: impl Fn(&T) -> R + Send + Sync + 'static,
+    ) -> SyncReader<R> {
+        let mapped = Arc::new(SyncObservable::new(f(&self.value())));
+        let value = Arc::downgrade(&self.value);
+        let weak = Arc::downgrade(&mapped);
+        let sub = self.on_updated(move || {
+            let (Some(value), Some(mapped)) = (value.upgrade(), weak.upgrade()) else {
+                return;
+            };
+            // The source is no longer locked when the mapped listeners are called
+            let result = f(&read(&value));
+            mapped.set(result);
+        });
+        SyncReader {
+            mapping: Some(Arc::new((mapped.clone(), sub))),
+            ..mapped.reader()
+        }
+    }
+}
+
+impl<T> Clone for SyncReader<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            listeners: self.listeners.clone(),
+            mapping: self.mapping.clone(),
+        }
+    }
+}
+
+impl<T: Default> Default for SyncObservable<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for SyncObservable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SyncObservable")
+            .field(&*self.value())
+            .finish()
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for SyncReader<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SyncReader").field(&*self.value()).finish()
+    }
+}
+
+fn read<T>(value: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    value.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn lock(listeners: &Listeners) -> MutexGuard<'_, Vec<Weak<Listener>>> {
+    listeners.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn on_updated(listeners: &Listeners, cb: impl Fn() + Send + Sync + 'static) -> SyncSubscription {
+    let cb: Arc<Listener> = Arc::new(cb);
+    lock(listeners).push(Arc::downgrade(&cb));
+    SyncSubscription { _cb: cb }
+}
+
+fn notify(listeners: &Listeners) -> usize {
+    let listeners: Vec<Arc<Listener>> = {
+        let mut listeners = lock(listeners);
+        listeners.retain(|listener| listener.strong_count() > 0);
+        listeners.iter().filter_map(Weak::upgrade).collect()
+    };
+    for listener in &listeners {
+        listener()
+    }
+    listeners.len()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use crate::SyncObservable;
+
+    #[test]
+    fn sync_observable_across_threads() {
+        let obs = SyncObservable::shared(0usize);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let sub = {
+            let calls = calls.clone();
+            obs.on_updated(move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let obs = obs.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        obs.update(|v| *v += 1);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(*obs.value(), 400);
+        assert_eq!(calls.load(Ordering::SeqCst), 400);
+
+        drop(sub);
+        obs.set(0);
+        assert_eq!(calls.load(Ordering::SeqCst), 400);
+        assert_eq!(obs.notify(), 0);
+    }
+
+    #[test]
+    fn sync_reader_once_and_map() {
+        let obs = SyncObservable::shared(1u32);
+        let reader = obs.reader();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let _once = {
+            let seen = seen.clone();
+            reader.once(move |v| seen.lock().unwrap().push(*v)).unwrap()
+        };
+
+        let doubled = obs.map(|v| *v * 2);
+        let _mapped = {
+            let seen = seen.clone();
+            doubled
+                .subscribe(move |v| seen.lock().unwrap().push(*v))
+                .unwrap()
+        };
+
+        let thread = {
+            let obs = obs.clone();
+            std::thread::spawn(move || obs.set(2))
+        };
+        thread.join().unwrap();
+        obs.set(3);
+        assert_eq!(*seen.lock().unwrap(), vec![2, 4, 6]);
+        assert_eq!(*doubled.value(), 6);
+
+        // The reader keeps the last value, but there is nothing left to listen to
+        drop(obs);
+        assert_eq!(*reader.value(), 3);
+        assert!(reader.on_updated(|| {}).is_none());
+        assert_eq!(*doubled.clone().value(), 6);
+    }
+}
@@ -43,6 +43,9 @@ impl<T> WeakRef<T> {
             phantom: PhantomData,
         })
     }
+    pub fn as_ptr(&self) -> *const T {
+        self.0.as_ptr()
+    }
 }
 impl<T> std::fmt::Debug for WeakRef<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -1,31 +1,70 @@
 use std::{
-    cell::{Ref, RefCell},
+    cell::{Cell, Ref, RefCell},
     rc::Rc,
 };
 
-use crate::Pushable;
+use crate::{Pushable, TryPushable};
 
 #[derive(Default)]
-pub struct Value<T>(RefCell<T>);
+pub struct Value<T> {
+    value: RefCell<T>,
+    /// Counts the writes, see generation
+    generation: Cell<usize>,
+}
 
 impl<T> Value<T> {
     pub fn new(value: T) -> Self {
-        Value(RefCell::new(value))
+        Value {
+            value: RefCell::new(value),
+            generation: Cell::new(0),
+        }
     }
     pub fn rc(value: T) -> Rc<Self> {
-        Rc::new(Value(RefCell::new(value)))
+        Rc::new(Self::new(value))
     }
     pub fn set(&self, value: T) {
-        self.0.replace(value);
+        self.replace(value);
+    }
+    /// Set the value, returning the previous one
+    pub fn replace(&self, value: T) -> T {
+        self.written();
+        self.value.replace(value)
+    }
+    pub fn get(&self) -> Ref<'_, T> {
+        self.value.borrow()
     }
-    pub fn get(&self) -> Ref<T> {
-        self.0.borrow()
+    /// Mutate the value in place. The mutable borrow ends when this returns
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.written();
+        f(&mut self.value.borrow_mut())
+    }
+    /// Moves on with every write, so that a MapReader can tell whether it was recalculated since
+    /// it was marked dirty
+    pub fn generation(&self) -> usize {
+        self.generation.get()
+    }
+    fn written(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1))
     }
 }
 
 impl<T: Pushable> Value<T> {
-    pub fn push(&self, value: T::Value) {
-        self.0.borrow_mut().push(value)
+    /// Returns whether the collection changed
+    pub fn push(&self, value: T::Value) -> bool {
+        self.written();
+        self.value.borrow_mut().push_changed(value)
+    }
+    /// Push all of the values, returning whether the collection changed
+    pub fn extend(&self, values: impl IntoIterator<Item = T::Value>) -> bool {
+        self.written();
+        self.value.borrow_mut().push_all(values)
+    }
+}
+
+impl<T: TryPushable> Value<T> {
+    pub fn try_push(&self, value: T::Value) -> Result<(), T::Value> {
+        self.written();
+        self.value.borrow_mut().try_push(value)
     }
 }
 
@@ -43,6 +82,13 @@ mod test {
         assert_eq!(*val.get(), 1);
     }
 
+    #[test]
+    fn update_in_place() {
+        let val = Value::new(vec![1]);
+        val.update(|v| v.push(2));
+        assert_eq!(*val.get(), vec![1, 2]);
+    }
+
     #[test]
     fn pushable_value() {
         let list = Value::new(vec![]);
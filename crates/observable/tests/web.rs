@@ -0,0 +1,101 @@
+//! Tests which need a wasm runtime. Run with `wasm-pack test --node --features async`
+
+#![cfg(all(target_arch = "wasm32", feature = "async"))]
+
+use std::{cell::RefCell, rc::Rc};
+
+use futures::{channel::oneshot, future::AbortHandle};
+use observable_rs::Observable;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+/// A computation waiting for the test to hand it a result
+type Pending = Vec<(u32, oneshot::Sender<String>)>;
+/// A computation which can be aborted, waiting for the test to let it finish
+type Cancelable = Vec<(AbortHandle, oneshot::Sender<()>)>;
+
+/// Let the spawned futures run
+async fn tick() {
+    JsFuture::from(js_sys::Promise::resolve(&JsValue::NULL))
+        .await
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn map_async_shows_placeholder_then_latest_result() {
+    let obs = Observable::new(1u32);
+
+    let pending: Rc<RefCell<Pending>> = Rc::default();
+    let mapped = {
+        let pending = pending.clone();
+        obs.reader()
+            .map_async_with_placeholder(String::from("loading"), move |v: &u32| {
+                let (tx, rx) = oneshot::channel();
+                pending.borrow_mut().push((*v, tx));
+                async move { rx.await.unwrap_or_default() }
+            })
+    };
+    assert_eq!(*mapped.value(), "loading");
+
+    let (v, tx) = pending.borrow_mut().remove(0);
+    tx.send(format!("resolved {}", v)).unwrap();
+    tick().await;
+    assert_eq!(*mapped.value(), "resolved 1");
+
+    // The computation for 2 is superseded by the one for 3 before it resolves
+    obs.set(2);
+    obs.set(3);
+    let mut senders: Pending = pending.borrow_mut().drain(..).collect();
+    let (v3, tx3) = senders.pop().unwrap();
+    let (v2, tx2) = senders.pop().unwrap();
+    assert_eq!((v2, v3), (2, 3));
+
+    tx3.send(format!("resolved {}", v3)).unwrap();
+    tick().await;
+    assert_eq!(*mapped.value(), "resolved 3");
+
+    let _ = tx2.send(format!("resolved {}", v2));
+    tick().await;
+    assert_eq!(*mapped.value(), "resolved 3");
+}
+
+#[wasm_bindgen_test]
+async fn map_async_cancelable_aborts_superseded_computation() {
+    let obs = Observable::new(1u32);
+
+    let log: Rc<RefCell<Vec<String>>> = Rc::default();
+    let pending: Rc<RefCell<Cancelable>> = Rc::default();
+    let mapped = {
+        let log = log.clone();
+        let pending = pending.clone();
+        obs.reader()
+            .map_async_cancelable(0, move |v: &u32, handle: AbortHandle| {
+                let v = *v;
+                let (tx, rx) = oneshot::channel();
+                pending.borrow_mut().push((handle, tx));
+                let log = log.clone();
+                async move {
+                    log.borrow_mut().push(format!("start {}", v));
+                    let _ = rx.await;
+                    log.borrow_mut().push(format!("done {}", v));
+                    v * 10
+                }
+            })
+    };
+    tick().await;
+
+    // A second change comes in before the first computation finishes
+    obs.set(2);
+    tick().await;
+    let (second, tx2) = pending.borrow_mut().pop().unwrap();
+    let (first, tx1) = pending.borrow_mut().pop().unwrap();
+    assert!(first.is_aborted());
+    assert!(!second.is_aborted());
+
+    let _ = tx1.send(());
+    tx2.send(()).unwrap();
+    tick().await;
+    assert_eq!(*mapped.value(), 20);
+    assert_eq!(*log.borrow(), vec!["start 1", "start 2", "done 2"]);
+}
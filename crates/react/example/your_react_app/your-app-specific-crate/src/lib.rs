@@ -1,6 +1,8 @@
 mod utils;
 
-use wasm_bindgen::prelude::*;
+use std::rc::Rc;
+
+use wasm_bindgen::{prelude::*, JsCast};
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
 #[cfg(feature = "wee_alloc")]
@@ -23,11 +25,16 @@ pub fn create_rust_thing() -> RustThing {
     RustThing::default()
 }
 
+/// Freeing a RustThing from JS tears it down: the subscriptions it made with follow are dropped
+/// right away, and its list is dropped along with its listeners. Any JsObservable handed out
+/// beforehand keeps reading the last value, and subscribing to it becomes a no-op
 #[wasm_bindgen]
 #[derive(Default)]
 pub struct RustThing {
     things: usize,
-    list: Observable<List<String>>,
+    list: Rc<Observable<List<String>>>,
+    /// The unsubscribe functions of the observables being followed
+    following: Vec<js_sys::Function>,
 }
 
 #[wasm_bindgen]
@@ -40,4 +47,25 @@ impl RustThing {
         let list = self.list.reader();
         list.into()
     }
+    /// Add each new value of `source` to the list, until this RustThing is freed
+    pub fn follow(&mut self, source: &mut JsObservable) {
+        let list = Rc::downgrade(&self.list);
+        let cb = Closure::wrap(Box::new(move |value: JsValue| {
+            if let (Some(list), Some(value)) = (list.upgrade(), value.as_string()) {
+                list.push(value)
+            }
+        }) as Box<dyn Fn(JsValue)>);
+        let unsubscribe = source.subscribe(cb.into_js_value().unchecked_into());
+        self.following.push(unsubscribe);
+    }
+}
+
+impl Drop for RustThing {
+    fn drop(&mut self) {
+        for unsubscribe in self.following.drain(..) {
+            if let Err(e) = unsubscribe.call0(&JsValue::UNDEFINED) {
+                log::error!("failed to unsubscribe: {:?}", e);
+            }
+        }
+    }
 }
@@ -3,11 +3,54 @@
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
+use wasm_bindgen::prelude::*;
 use wasm_bindgen_test::*;
 
+use observable_react::JsObservable;
+use observable_rs::Observable;
+use your_app_specific_crate::create_rust_thing;
+
 wasm_bindgen_test_configure!(run_in_browser);
 
 #[wasm_bindgen_test]
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+#[wasm_bindgen_test]
+fn list_outlives_rust_thing() {
+    let mut thing = create_rust_thing();
+    thing.do_something();
+    thing.do_something();
+
+    let mut list = thing.get_the_list();
+    drop(thing);
+
+    let value: js_sys::Array = list.get().into();
+    assert_eq!(value.length(), 2);
+    assert_eq!(value.get(1), JsValue::from_str("Thing 2"));
+
+    // The writer is gone, so there is nothing to subscribe to, but that should not panic
+    let unsub = list.subscribe(js_sys::Function::new_no_args(""));
+    unsub.call0(&JsValue::UNDEFINED).unwrap();
+}
+
+#[wasm_bindgen_test]
+fn free_releases_followed_observables() {
+    let source = Observable::new(String::new());
+    let mut thing = create_rust_thing();
+    thing.follow(&mut JsObservable::from(source.reader()));
+    assert_eq!(source.listener_count(), 1);
+
+    source.set("a".into());
+    let list = thing.get_the_list();
+    assert_eq!(js_sys::Array::from(&list.get()).length(), 1);
+
+    // Freeing from JS runs the same Drop
+    drop(thing);
+    assert_eq!(source.listener_count(), 0);
+    source.set("b".into());
+    let value = js_sys::Array::from(&list.get());
+    assert_eq!(value.length(), 1);
+    assert_eq!(value.get(0), JsValue::from_str("a"));
+}
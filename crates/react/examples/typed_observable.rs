@@ -0,0 +1,43 @@
+//! Giving an observable a TS type derived with tsify. Build with the tsify feature, for
+//! wasm32-unknown-unknown, and run wasm-bindgen over the result. The declarations then read
+//! `export function catState(): TypedObservable<CatState>`, along with the CatState interface
+//! ```text
+//! cargo build --example typed_observable --features tsify --target wasm32-unknown-unknown
+//! ```
+
+use observable_react::JsObservable;
+use observable_rs::Observable;
+use serde::Serialize;
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Serialize, Tsify)]
+pub struct CatState {
+    pub cats: u32,
+    pub names: Vec<String>,
+}
+
+thread_local! {
+    static CATS: Observable<CatState> = Observable::new(CatState {
+        cats: 1,
+        names: vec!["Tom".into()],
+    });
+}
+
+#[wasm_bindgen(js_name = catState, unchecked_return_type = "TypedObservable<CatState>")]
+pub fn cat_state() -> JsObservable {
+    CATS.with(|cats| JsObservable::tsify(cats.reader()))
+}
+
+#[wasm_bindgen(js_name = adoptCat)]
+pub fn adopt_cat(name: String) {
+    CATS.with(|cats| {
+        cats.update(|state| {
+            state.cats += 1;
+            state.names.push(name);
+        })
+    })
+}
+
+#[allow(dead_code)]
+fn main() {}
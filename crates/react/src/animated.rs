@@ -0,0 +1,94 @@
+//! Tween numeric observables, see JsObservable::animated
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+use observable_rs::Observable;
+use wasm_bindgen::JsValue;
+
+use crate::{derived::JsDerived, scheduler::scheduler, traits::JsObserve};
+
+pub(crate) type Easing = Rc<dyn Fn(f64) -> f64>;
+
+/// The tween in progress
+struct Tween {
+    from: f64,
+    to: f64,
+    start: f64,
+}
+
+struct Animation {
+    obs: Rc<Observable<JsValue>>,
+    duration_ms: f64,
+    easing: Easing,
+    tween: RefCell<Option<Tween>>,
+    /// Whether a frame has been requested
+    scheduled: Cell<bool>,
+}
+
+impl Animation {
+    fn retarget(self: &Rc<Self>, target: JsValue) {
+        let (Some(to), Some(from)) = (target.as_f64(), self.obs.value().as_f64()) else {
+            // Only numbers can be tweened, anything else is shown right away
+            self.tween.take();
+            self.obs.set(target);
+            return;
+        };
+        self.tween.replace(Some(Tween {
+            from,
+            to,
+            start: scheduler().now(),
+        }));
+        if !self.scheduled.replace(true) {
+            Self::request_frame(Rc::downgrade(self));
+        }
+    }
+
+    fn request_frame(animation: Weak<Self>) {
+        scheduler().animation_frame(Box::new(move || {
+            if let Some(animation) = animation.upgrade() {
+                animation.frame();
+            }
+        }));
+    }
+
+    fn frame(self: &Rc<Self>) {
+        let Some((value, done)) = self.tween.borrow().as_ref().map(|tween| {
+            let progress = if self.duration_ms > 0.0 {
+                ((scheduler().now() - tween.start) / self.duration_ms).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            let value = tween.from + (tween.to - tween.from) * (self.easing)(progress);
+            (value, progress >= 1.0)
+        }) else {
+            self.scheduled.set(false);
+            return;
+        };
+
+        if done {
+            self.tween.take();
+            self.scheduled.set(false);
+        } else {
+            Self::request_frame(Rc::downgrade(self));
+        }
+        self.obs.set(value.into());
+    }
+}
+
+/// A JsDerived which follows `source`, tweening numeric values over `duration_ms`
+pub(crate) fn animate(source: &dyn JsObserve, duration_ms: f64, easing: Easing) -> JsDerived {
+    let obs = Rc::new(Observable::new(source.get_js()));
+    let animation = Rc::new(Animation {
+        obs: obs.clone(),
+        duration_ms,
+        easing,
+        tween: RefCell::new(None),
+        scheduled: Cell::new(false),
+    });
+    // The subscription owns the animation, whose pending frames only hold it weakly
+    let sub = source.subscribe(Box::new(move |target: JsValue| animation.retarget(target)));
+    JsDerived::from_parts(obs, sub)
+}
@@ -0,0 +1,10 @@
+/// Something to re-render when an observable changes, such as a component of some UI framework
+pub trait Binding {
+    fn request_update(&self);
+}
+
+impl<F: Fn()> Binding for F {
+    fn request_update(&self) {
+        self()
+    }
+}
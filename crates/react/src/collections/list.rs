@@ -1,27 +1,162 @@
-use std::ops::Deref;
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    ops::Deref,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
-use observable_rs::Pushable;
+use js_sys::Array;
+use observable_rs::{Observable, Pushable, Reader, Subscription};
 use wasm_bindgen::JsValue;
 
-pub struct List<T>(Vec<T>);
+pub struct List<T> {
+    items: Vec<T>,
+    /// Changes whenever the list changes other than by being appended to, so that a JS copy of it
+    /// knows whether it can catch up by pushing the new items
+    revision: u64,
+    /// Identifies this list, as opposed to one which replaced it
+    id: u64,
+    /// The most recent changes, see subscribe_changes
+    changes: VecDeque<ListChange>,
+    /// How many changes were ever made, including those which have fallen out of the log
+    change_count: u64,
+}
+
+/// A single change to a List, for updating a copy of it in place rather than rebuilding it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListChange {
+    Push {
+        index: usize,
+    },
+    Remove {
+        index: usize,
+    },
+    Clear,
+    /// The list was replaced, or changed more than could be kept track of. Start over from its value
+    Reset,
+}
+
+/// How many changes are kept for subscribers which haven't been notified of them yet. Those which
+/// fall further behind than this, for instance during a large batch, are sent a Reset
+const CHANGE_LOG: usize = 64;
+
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+impl<T> List<T> {
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.items.len() {
+            return None;
+        }
+        self.revision = next_revision();
+        self.record(ListChange::Remove { index });
+        Some(self.items.remove(index))
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        let item = self.items.pop()?;
+        self.revision = next_revision();
+        self.record(ListChange::Remove {
+            index: self.items.len(),
+        });
+        Some(item)
+    }
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.revision = next_revision();
+        self.record(ListChange::Clear);
+    }
+    fn record(&mut self, change: ListChange) {
+        if self.changes.len() == CHANGE_LOG {
+            self.changes.pop_front();
+        }
+        self.changes.push_back(change);
+        self.change_count += 1;
+    }
+    /// The changes made after the first `seen`, or None if they are no longer all in the log
+    fn changes_since(&self, seen: u64) -> Option<impl Iterator<Item = &ListChange>> {
+        let missed = usize::try_from(self.change_count.checked_sub(seen)?).ok()?;
+        let skip = self.changes.len().checked_sub(missed)?;
+        Some(self.changes.iter().skip(skip))
+    }
+}
+
+/// Removals for an Observable<List<T>>, each of which notifies once if anything was removed
+pub trait ObservableList<T> {
+    fn remove_at(&self, index: usize) -> Option<T>;
+    fn pop(&self) -> Option<T>;
+    fn clear(&self);
+    /// Call `cb` with each change to the list, in order, as an alternative to rebuilding from its
+    /// whole value. Several changes made in a batch are delivered together
+    fn subscribe_changes(&self, cb: impl Fn(&ListChange) + 'static) -> Subscription;
+}
+
+impl<T: 'static> ObservableList<T> for Observable<List<T>> {
+    fn remove_at(&self, index: usize) -> Option<T> {
+        let mut removed = None;
+        self.modify(|list| {
+            removed = list.remove(index);
+            removed.is_some()
+        });
+        removed
+    }
+    fn pop(&self) -> Option<T> {
+        let mut removed = None;
+        self.modify(|list| {
+            removed = list.pop();
+            removed.is_some()
+        });
+        removed
+    }
+    fn clear(&self) {
+        self.modify(|list| {
+            let changed = !list.is_empty();
+            list.clear();
+            changed
+        });
+    }
+    fn subscribe_changes(&self, cb: impl Fn(&ListChange) + 'static) -> Subscription {
+        let seen = {
+            let list = self.value();
+            Cell::new((list.id, list.change_count))
+        };
+        self.subscribe(move |list: &List<T>| {
+            let (id, count) = seen.replace((list.id, list.change_count));
+            match list.changes_since(count) {
+                Some(changes) if id == list.id => changes.for_each(&cb),
+                _ => cb(&ListChange::Reset),
+            }
+        })
+    }
+}
 
 impl<T> Deref for List<T> {
     type Target = Vec<T>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.items
     }
 }
 
 impl<T> Default for List<T> {
     fn default() -> Self {
-        Self(Default::default())
+        Vec::new().into()
     }
 }
 
 impl<T> From<Vec<T>> for List<T> {
-    fn from(value: Vec<T>) -> Self {
-        Self(value)
+    fn from(items: Vec<T>) -> Self {
+        let revision = next_revision();
+        Self {
+            items,
+            revision,
+            id: revision,
+            changes: VecDeque::new(),
+            change_count: 0,
+        }
     }
 }
 
@@ -29,7 +164,67 @@ impl<T> Pushable for List<T> {
     type Value = T;
 
     fn push(&mut self, value: Self::Value) {
-        self.0.push(value)
+        self.items.push(value);
+        self.record(ListChange::Push {
+            index: self.items.len() - 1,
+        });
+    }
+}
+
+/// The JS side of a Reader<List<T>>. While the list is only appended to, just the new items are
+/// converted, and pushed onto a cached JS array. Every value handed out is a copy of it though, so
+/// that React sees a new array, and earlier values are never changed underneath whoever holds them.
+/// Any other change builds the array from scratch
+pub struct ListReader<T> {
+    pub(crate) reader: Reader<List<T>>,
+    cache: Rc<RefCell<Option<ListCache>>>,
+}
+
+struct ListCache {
+    array: Array,
+    revision: u64,
+}
+
+impl<T> ListReader<T>
+where
+    T: Into<JsValue> + Clone,
+{
+    pub fn new(reader: Reader<List<T>>) -> Self {
+        ListReader {
+            reader,
+            cache: Rc::default(),
+        }
+    }
+    pub(crate) fn array(&self, list: &List<T>) -> Array {
+        let mut cache = self.cache.borrow_mut();
+        match &*cache {
+            Some(cached)
+                if cached.revision == list.revision
+                    && cached.array.length() as usize <= list.len() =>
+            {
+                for item in &list[cached.array.length() as usize..] {
+                    cached.array.push(&item.clone().into());
+                }
+                cached.array.slice(0, cached.array.length())
+            }
+            _ => {
+                let array: Array = JsValue::from(list).into();
+                *cache = Some(ListCache {
+                    array: array.slice(0, array.length()),
+                    revision: list.revision,
+                });
+                array
+            }
+        }
+    }
+}
+
+impl<T> Clone for ListReader<T> {
+    fn clone(&self) -> Self {
+        ListReader {
+            reader: self.reader.clone(),
+            cache: self.cache.clone(),
+        }
     }
 }
 
@@ -39,7 +234,7 @@ where
 {
     fn from(value: &List<T>) -> Self {
         let array = js_sys::Array::new();
-        for v in value.0.iter() {
+        for v in value.items.iter() {
             let v = v.clone();
             let v: JsValue = v.into();
             array.push(&v);
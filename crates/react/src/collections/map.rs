@@ -0,0 +1,77 @@
+use std::{collections::BTreeMap, ops::Deref};
+
+use observable_rs::Observable;
+use wasm_bindgen::JsValue;
+
+/// A keyed collection, which reaches JS as a Map with its entries ordered by key
+pub struct Map<K, V>(BTreeMap<K, V>);
+
+impl<K: Ord, V> Map<K, V> {
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.0.insert(key, value)
+    }
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.0.remove(key)
+    }
+}
+
+impl<K, V> Deref for Map<K, V> {
+    type Target = BTreeMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<K, V> From<BTreeMap<K, V>> for Map<K, V> {
+    fn from(value: BTreeMap<K, V>) -> Self {
+        Self(value)
+    }
+}
+
+/// Inserts and removals for an Observable<Map<K, V>>, each of which notifies once
+pub trait ObservableMap<K, V> {
+    /// Insert the entry, returning the value it replaced
+    fn insert(&self, key: K, value: V) -> Option<V>;
+    /// Remove the entry, notifying only if there was one
+    fn remove(&self, key: &K) -> Option<V>;
+}
+
+impl<K: Ord, V> ObservableMap<K, V> for Observable<Map<K, V>> {
+    fn insert(&self, key: K, value: V) -> Option<V> {
+        let mut replaced = None;
+        self.modify(|map| {
+            replaced = map.insert(key, value);
+            true
+        });
+        replaced
+    }
+    fn remove(&self, key: &K) -> Option<V> {
+        let mut removed = None;
+        self.modify(|map| {
+            removed = map.remove(key);
+            removed.is_some()
+        });
+        removed
+    }
+}
+
+impl<K, V> From<&Map<K, V>> for JsValue
+where
+    K: Into<JsValue> + Clone,
+    V: Into<JsValue> + Clone,
+{
+    fn from(value: &Map<K, V>) -> Self {
+        let map = js_sys::Map::new();
+        for (k, v) in value.0.iter() {
+            map.set(&k.clone().into(), &v.clone().into());
+        }
+        map.into()
+    }
+}
@@ -1,3 +1,5 @@
 mod list;
+mod map;
 
 pub use list::*;
+pub use map::*;
@@ -0,0 +1,54 @@
+//! Describes a change to JS subscribers, alongside the new value
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const CHANGE_CONTEXT_TS: &'static str = r#"
+export type ChangeKind = "change" | "initial";
+export type Subscriber<T> = (value: T, context: ChangeContext) => void;
+"#;
+
+/// Passed as the second argument to JS subscribers
+#[wasm_bindgen]
+pub struct ChangeContext {
+    generation: u32,
+    kind: &'static str,
+    value: JsValue,
+    previous: JsValue,
+}
+
+impl ChangeContext {
+    pub fn new(generation: u32, kind: &'static str, value: JsValue, previous: JsValue) -> Self {
+        ChangeContext {
+            generation,
+            kind,
+            value,
+            previous,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl ChangeContext {
+    /// Counts the changes delivered to this subscriber, starting at 1. The current value delivered by
+    /// subscribeImmediate is generation 0
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+    /// "initial" for the current value delivered by subscribeImmediate, "change" otherwise
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.kind.to_string()
+    }
+    /// The value being delivered, which is also passed as the first argument
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> JsValue {
+        self.value.clone()
+    }
+    /// The value delivered to this subscriber before this one, or undefined if there was none
+    #[wasm_bindgen(getter)]
+    pub fn previous(&self) -> JsValue {
+        self.previous.clone()
+    }
+}
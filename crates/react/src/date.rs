@@ -0,0 +1,52 @@
+//! Bind time valued observables to JS Dates
+
+use chrono::{DateTime, TimeZone, Utc};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// A UTC timestamp which converts to and from a JS Date. Time zones don't survive the trip:
+/// a JS Date is just an instant, which comes back as UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JsDateTime(pub DateTime<Utc>);
+
+impl JsDateTime {
+    /// The same instant in UTC
+    pub fn from_zoned<Tz: TimeZone>(time: &DateTime<Tz>) -> Self {
+        JsDateTime(time.with_timezone(&Utc))
+    }
+}
+
+impl From<DateTime<Utc>> for JsDateTime {
+    fn from(time: DateTime<Utc>) -> Self {
+        JsDateTime(time)
+    }
+}
+
+impl From<JsDateTime> for js_sys::Date {
+    fn from(time: JsDateTime) -> Self {
+        js_sys::Date::new(&(time.0.timestamp_millis() as f64).into())
+    }
+}
+
+impl From<JsDateTime> for JsValue {
+    fn from(time: JsDateTime) -> Self {
+        js_sys::Date::from(time).into()
+    }
+}
+
+/// Fails for values which are not Dates, and for invalid Dates
+impl TryFrom<JsValue> for JsDateTime {
+    type Error = JsValue;
+
+    fn try_from(value: JsValue) -> Result<Self, Self::Error> {
+        let Some(date) = value.dyn_ref::<js_sys::Date>() else {
+            return Err(value);
+        };
+        let millis = date.get_time();
+        if millis.is_nan() {
+            return Err(value);
+        }
+        DateTime::from_timestamp_millis(millis as i64)
+            .map(JsDateTime)
+            .ok_or(value)
+    }
+}
@@ -0,0 +1,196 @@
+use std::rc::Rc;
+
+use js_sys::{Array, Function, Object, Reflect};
+use observable_rs::{Observable, Reader, Subscription};
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+
+use crate::{console_error, traits::JsObserve};
+
+/// A JS value derived from another JsObserve by a JS function, recomputed whenever the source changes
+#[derive(Clone)]
+pub struct JsDerived {
+    obs: Rc<Observable<JsValue>>,
+    // Keeps the source subscription, and with it the JS function, alive for our lifetime
+    #[allow(dead_code)]
+    sub: Rc<Option<Subscription>>,
+    // Same for sources which were subscribed to through JS, as with combine
+    #[allow(dead_code)]
+    js_subs: Option<Rc<JsSubscriptions>>,
+}
+
+/// Subscriptions made through the JS subscribe method, which are unsubscribed on drop
+struct JsSubscriptions {
+    unsubscribes: Vec<Function>,
+    _callback: Closure<dyn Fn()>,
+}
+
+impl Drop for JsSubscriptions {
+    fn drop(&mut self) {
+        for unsubscribe in &self.unsubscribes {
+            let _ = unsubscribe.call0(&JsValue::UNDEFINED);
+        }
+    }
+}
+
+impl JsDerived {
+    pub fn new(source: &dyn JsObserve, cb: Function) -> Self {
+        Self::build(source, cb, false)
+    }
+    /// Like new, but only notifies when the derived value is not deep-equal to the previous one
+    pub fn select(source: &dyn JsObserve, cb: Function) -> Self {
+        Self::build(source, cb, true)
+    }
+    fn build(source: &dyn JsObserve, cb: Function, distinct: bool) -> Self {
+        let derive = move |value: &JsValue| match cb.call1(&JsValue::UNDEFINED, value) {
+            Ok(derived) => Some(derived),
+            Err(err) => {
+                console_error(&err);
+                None
+            }
+        };
+
+        let obs = Rc::new(Observable::new(
+            derive(&source.get_js()).unwrap_or(JsValue::UNDEFINED),
+        ));
+        let sub = {
+            let obs = Rc::downgrade(&obs);
+            source.subscribe(Box::new(move |value: JsValue| {
+                let Some(obs) = obs.upgrade() else { return };
+                // A failed derivation keeps the last good value
+                let Some(derived) = derive(&value) else {
+                    return;
+                };
+                if distinct && deep_equal(&obs.value(), &derived) {
+                    return;
+                }
+                obs.set(derived);
+            }))
+        };
+
+        JsDerived {
+            obs,
+            sub: Rc::new(sub),
+            js_subs: None,
+        }
+    }
+    /// Call `combiner` with the values of all `sources`, now and whenever any of them changes.
+    /// Sources are used through their JS get and subscribe methods, so any JsObservable will do
+    pub fn combine(sources: Array, combiner: Function) -> Self {
+        let source_list: Vec<JsValue> = sources.iter().collect();
+        let combine = move || {
+            let values: Array = sources
+                .iter()
+                .map(|source| call_method(&source, "get", &[]).unwrap_or(JsValue::UNDEFINED))
+                .collect();
+            combiner
+                .apply(&JsValue::UNDEFINED, &values)
+                .map_err(|err| console_error(&err))
+                .ok()
+        };
+
+        let obs = Rc::new(Observable::new(combine().unwrap_or(JsValue::UNDEFINED)));
+        let callback = {
+            let obs = Rc::downgrade(&obs);
+            Closure::wrap(Box::new(move || {
+                let Some(obs) = obs.upgrade() else { return };
+                if let Some(combined) = combine() {
+                    obs.set(combined);
+                }
+            }) as Box<dyn Fn()>)
+        };
+        let unsubscribes = source_list
+            .iter()
+            .filter_map(|source| {
+                match call_method(source, "subscribe", &[callback.as_ref().clone()]) {
+                    Ok(unsubscribe) => unsubscribe.dyn_into::<Function>().ok(),
+                    Err(err) => {
+                        console_error(&err);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        JsDerived {
+            obs,
+            sub: Rc::new(None),
+            js_subs: Some(Rc::new(JsSubscriptions {
+                unsubscribes,
+                _callback: callback,
+            })),
+        }
+    }
+    /// A derived value maintained by `sub`, which is kept for as long as the JsDerived is alive
+    pub(crate) fn from_parts(obs: Rc<Observable<JsValue>>, sub: Option<Subscription>) -> Self {
+        JsDerived {
+            obs,
+            sub: Rc::new(sub),
+            js_subs: None,
+        }
+    }
+    fn reader(&self) -> Reader<JsValue> {
+        self.obs.reader()
+    }
+}
+
+impl JsObserve for JsDerived {
+    fn get_js(&self) -> JsValue {
+        self.reader().get_js()
+    }
+
+    fn subscribe(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        JsObserve::subscribe(&self.reader(), cb)
+    }
+
+    fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        JsObserve::once(&self.reader(), cb)
+    }
+
+    fn on_complete(&self, cb: Box<dyn FnOnce()>) -> Option<Subscription> {
+        JsObserve::on_complete(&self.reader(), cb)
+    }
+}
+
+/// Call the method `name` of a JS object
+fn call_method(obj: &JsValue, name: &str, args: &[JsValue]) -> Result<JsValue, JsValue> {
+    let method: Function = Reflect::get(obj, &name.into())?.dyn_into()?;
+    method.apply(obj, &args.iter().collect())
+}
+
+/// Structural equality for plain JS data: primitives are compared with Object.is,
+/// arrays and objects by comparing their own enumerable properties recursively. Cyclic values are
+/// handled by treating a pair which is already being compared further up as equal
+pub fn deep_equal(a: &JsValue, b: &JsValue) -> bool {
+    deep_equal_visiting(a, b, &mut Vec::new())
+}
+
+fn deep_equal_visiting(a: &JsValue, b: &JsValue, visiting: &mut Vec<(JsValue, JsValue)>) -> bool {
+    if Object::is(a, b) {
+        return true;
+    }
+    if !a.is_object() || !b.is_object() || Array::is_array(a) != Array::is_array(b) {
+        return false;
+    }
+    if visiting
+        .iter()
+        .any(|(va, vb)| Object::is(va, a) && Object::is(vb, b))
+    {
+        return true;
+    }
+
+    let a_keys = Object::keys(a.unchecked_ref::<Object>());
+    let b_keys = Object::keys(b.unchecked_ref::<Object>());
+    if a_keys.length() != b_keys.length() {
+        return false;
+    }
+    visiting.push((a.clone(), b.clone()));
+    let equal = a_keys.iter().all(|key| {
+        Reflect::has(b, &key).unwrap_or(false)
+            && match (Reflect::get(a, &key), Reflect::get(b, &key)) {
+                (Ok(a), Ok(b)) => deep_equal_visiting(&a, &b, visiting),
+                _ => false,
+            }
+    });
+    visiting.pop();
+    equal
+}
@@ -4,14 +4,137 @@
 //!
 //! ```
 
+mod animated;
+pub mod binding;
 pub mod collections;
+pub mod context;
+#[cfg(feature = "chrono")]
+pub mod date;
+pub mod derived;
 pub mod impls;
+mod mount;
+mod proxy;
 pub mod react;
+pub mod scheduler;
+pub mod serializer;
 pub mod traits;
+mod visibility;
+pub mod websocket;
 
-use crate::traits::JsObserve;
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    rc::Rc,
+};
+
+use crate::{
+    binding::Binding,
+    collections::{List, ListReader},
+    context::ChangeContext,
+    derived::JsDerived,
+    mount::Mount,
+    react::ReactComponent,
+    scheduler::scheduler,
+    serializer::{Serialized, Serializer},
+    traits::JsObserve,
+    visibility::{is_hidden, VisibilityListener},
+};
+use observable_rs::{LazyMapReader, MapReader, Reader, Subscription};
 use wasm_bindgen::{prelude::*, JsValue};
 
+#[wasm_bindgen]
+extern "C" {
+    /// Binding to console.error
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(msg: &JsValue);
+
+    /// Binding to requestAnimationFrame
+    #[wasm_bindgen(js_name = requestAnimationFrame)]
+    fn request_animation_frame(cb: &JsValue) -> JsValue;
+
+    /// Binding to requestIdleCallback
+    #[wasm_bindgen(js_name = requestIdleCallback)]
+    fn request_idle_callback(cb: &JsValue) -> JsValue;
+
+    /// Binding to structuredClone
+    #[wasm_bindgen(js_name = structuredClone, catch)]
+    fn structured_clone(value: &JsValue) -> Result<JsValue, JsValue>;
+
+    /// Binding to queueMicrotask
+    #[wasm_bindgen(js_name = queueMicrotask)]
+    fn queue_microtask(cb: &JsValue);
+
+    /// Binding to setTimeout
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(cb: &JsValue, ms: i32) -> JsValue;
+}
+
+/// Deliver a value to a JS subscriber. A misbehaving subscriber must not take down
+/// the other subscribers in the same notify pass, so failures are logged rather than propagated
+fn call_subscriber(cb: &js_sys::Function, v: &JsValue, type_name: &str) {
+    call_subscriber_with(cb, v, &JsValue::UNDEFINED, type_name)
+}
+
+/// Like call_subscriber, passing `ctx` as the second argument. Whatever the subscriber throws is
+/// caught by call2 and logged. Panics are only caught on native targets: wasm32 builds abort on
+/// panic, so there catch_unwind could never return Err and is left out
+fn call_subscriber_with(cb: &js_sys::Function, v: &JsValue, ctx: &JsValue, type_name: &str) {
+    #[cfg(not(target_arch = "wasm32"))]
+    let result = {
+        use std::panic::{self, AssertUnwindSafe};
+        match panic::catch_unwind(AssertUnwindSafe(|| cb.call2(&JsValue::UNDEFINED, v, ctx))) {
+            Ok(result) => result,
+            Err(payload) => {
+                let msg = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("unknown panic");
+                console_error(&format!("subscriber of {} panicked: {}", type_name, msg).into());
+                return;
+            }
+        }
+    };
+    #[cfg(target_arch = "wasm32")]
+    let result = cb.call2(&JsValue::UNDEFINED, v, ctx);
+    if let Err(err) = result {
+        console_error(&format!("subscriber of {} threw", type_name).into());
+        console_error(&err);
+    }
+}
+
+/// Object.freeze the value and everything reachable from it. Parents which are already frozen are
+/// still descended into, as they may only be frozen shallowly. `visited` guards against cycles
+fn deep_freeze(v: &JsValue, visited: &js_sys::Set) {
+    if !v.is_object() && !v.is_function() || visited.has(v) || is_wasm_bindgen_instance(v) {
+        return;
+    }
+    visited.add(v);
+    let object: &js_sys::Object = v.unchecked_ref();
+    js_sys::Object::freeze(object);
+    for child in js_sys::Object::values(object).iter() {
+        deep_freeze(&child, visited);
+    }
+}
+
+/// Exported Rust structs write to their pointer when freed, so they must never be frozen
+fn is_wasm_bindgen_instance(v: &JsValue) -> bool {
+    v.is_object()
+        && js_sys::Object::has_own(v.unchecked_ref::<js_sys::Object>(), &"__wbg_ptr".into())
+}
+
+// JsObservable itself is untyped. Rust functions can give their return value the type of its
+// contents with `#[wasm_bindgen(unchecked_return_type = "TypedObservable<CatState>")]`, using any
+// type the bindings define, such as those derived with tsify. See JsObservable::tsify
+#[wasm_bindgen(typescript_custom_section)]
+const TYPED_OBSERVABLE_TS: &'static str = r#"
+export interface TypedObservable<T> extends JsObservable {
+  get(): T;
+  readonly value: T;
+  subscribe(cb: Subscriber<T>): () => void;
+  subscribeImmediate(cb: Subscriber<T>): () => void;
+}
+"#;
+
 /// # Wrapper around Observable<T> for usage in javascript/typescript
 /// ```
 ///
@@ -75,11 +198,58 @@ use wasm_bindgen::{prelude::*, JsValue};
 #[wasm_bindgen]
 pub struct JsObservable {
     obs: Box<dyn JsObserve>,
+    /// Holds the getters registered with with_computed. Created on first use
+    computed: OnceCell<js_sys::Object>,
 }
 
 impl JsObservable {
     pub fn new(obs: Box<dyn JsObserve>) -> Self {
-        JsObservable { obs }
+        JsObservable {
+            obs,
+            computed: OnceCell::new(),
+        }
+    }
+    /// Subscribe `cb`, with `previous` as the value it was last given
+    fn subscribe_after(&self, cb: js_sys::Function, previous: JsValue) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let generation = Cell::new(0);
+        let previous = RefCell::new(previous);
+        let clean_up = self.obs.subscribe(Box::new(move |v: JsValue| {
+            generation.set(generation.get() + 1);
+            let last = previous.replace(v.clone());
+            let ctx = ChangeContext::new(generation.get(), "change", v.clone(), last);
+            call_subscriber_with(&cb, &v, &ctx.into(), type_name);
+        }));
+
+        let unsub = Closure::once_into_js(Box::new(move || drop(clean_up)) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+    /// Expose a reader whose values are converted with `serializer`, rather than with Into<JsValue>
+    pub fn with_serializer<T: 'static>(
+        reader: Reader<T>,
+        serializer: impl Serializer<T> + 'static,
+    ) -> Self {
+        Serialized::new(reader, Rc::new(serializer)).into()
+    }
+    /// Expose a reader whose values are converted with their Tsify impl, so that they match the
+    /// declaration derived for them. Declare the function returning it with
+    /// `#[wasm_bindgen(unchecked_return_type = "TypedObservable<CatState>")]` to type get, value
+    /// and subscribe in TS. See examples/typed_observable.rs
+    #[cfg(feature = "tsify")]
+    pub fn tsify<T: tsify::Tsify + serde::Serialize + 'static>(reader: Reader<T>) -> Self {
+        Self::with_serializer(reader, serializer::TsifyJs)
+    }
+    /// A live view of the value, see as_proxy. Writes are handed to `set` along with the path of
+    /// the written property
+    pub fn proxy_with(&self, set: impl Fn(&[String], JsValue) + 'static) -> js_sys::Proxy {
+        let obs = dyn_clone::clone_box(&*self.obs);
+        proxy::live_proxy(Rc::new(move || obs.get_js()), Rc::new(set), Vec::new())
+    }
+    /// Request an update of the binding whenever this observable changes, for as long as the subscription is kept
+    pub fn bind(&self, binding: impl Binding + 'static) -> Option<Subscription> {
+        self.obs
+            .subscribe(Box::new(move |_: JsValue| binding.request_update()))
     }
 }
 
@@ -88,16 +258,162 @@ impl JsObservable {
     pub fn get(&self) -> JsValue {
         self.obs.get_js()
     }
-    pub fn map(&self, cb: js_sys::Function) -> JsValue {
+    /// A structuredClone of the current value, which JS can mutate without affecting the source.
+    /// Throws if the value can't be cloned, as is the case for functions and exported Rust structs
+    #[wasm_bindgen(js_name = getClone)]
+    pub fn get_clone(&self) -> Result<JsValue, JsValue> {
+        structured_clone(&self.obs.get_js())
+    }
+    /// A Proxy which reads through to the current value, and passes writes to
+    /// `setter(path, value)`, `path` being the array of keys leading to the written property.
+    /// Nested objects are proxied too, so `proxy.a.b = 1` calls `setter(["a", "b"], 1)`.
+    /// The value itself is not modified, the setter is expected to set the observable
+    #[wasm_bindgen(js_name = asProxy)]
+    pub fn as_proxy(&self, setter: js_sys::Function) -> js_sys::Proxy {
+        self.proxy_with(move |path: &[String], value: JsValue| {
+            let path: js_sys::Array = path.iter().map(|key| JsValue::from_str(key)).collect();
+            if let Err(e) = setter.call2(&JsValue::UNDEFINED, &path, &value) {
+                console_error(&e);
+            }
+        })
+    }
+    /// An iterator over the items of the current value, for list observables, as in
+    /// `for (const item of obs.values())`. The items are copied when this is called, so later
+    /// changes don't show up in it. Values which are neither iterable nor array-like have no items
+    pub fn values(&self) -> js_sys::Iterator {
+        js_sys::Array::from(&self.obs.get_js()).values()
+    }
+    /// Call `cb` with the current value, returning an array of what it returned. Whatever `cb`
+    /// throws is rethrown
+    pub fn map(&self, cb: js_sys::Function) -> Result<JsValue, JsValue> {
         self.obs.map_js(cb)
     }
-    pub fn subscribe(
+    /// Derive a new observable by calling `cb` with this observable's value, now and on every change
+    pub fn derive(&self, cb: js_sys::Function) -> JsObservable {
+        JsDerived::new(&*self.obs, cb).into()
+    }
+    /// Derive an observable which tweens to each new value of this one over `duration_ms`, emitting
+    /// the values in between on every animation frame. The easing is linear. Only numbers are
+    /// tweened, other values are passed through right away
+    pub fn animated(&self, duration_ms: f64) -> JsObservable {
+        animated::animate(&*self.obs, duration_ms, Rc::new(|t| t)).into()
+    }
+    /// Like animated, with an easing function mapping the progress of the tween, from 0 to 1, onto
+    /// how far along the value should be
+    #[wasm_bindgen(js_name = animatedWith)]
+    pub fn animated_with(&self, duration_ms: f64, easing: js_sys::Function) -> JsObservable {
+        let easing = move |t: f64| {
+            easing
+                .call1(&JsValue::UNDEFINED, &t.into())
+                .ok()
+                .and_then(|eased| eased.as_f64())
+                .unwrap_or(t)
+        };
+        animated::animate(&*self.obs, duration_ms, Rc::new(easing)).into()
+    }
+    /// Derive a new observable by applying `selector` to this observable's value, which only notifies
+    /// when the selected value changes (by deep equality). Like the Redux useSelector pattern
+    pub fn select(&self, selector: js_sys::Function) -> JsObservable {
+        JsDerived::select(&*self.obs, selector).into()
+    }
+    /// Call `cb` with the new value and a ChangeContext on every change. Returns the unsubscribe function
+    pub fn subscribe(&mut self, cb: js_sys::Function) -> js_sys::Function {
+        self.subscribe_after(cb, JsValue::UNDEFINED)
+    }
+
+    /// Like subscribe, but `cb` is first called with the current value, before this returns. If that
+    /// call throws, the error is rethrown and no subscription is made
+    #[wasm_bindgen(js_name = subscribeImmediate)]
+    pub fn subscribe_immediate(
+        &mut self,
+        cb: js_sys::Function,
+    ) -> Result<js_sys::Function, JsValue> {
+        let current = self.obs.get_js();
+        let ctx = ChangeContext::new(0, "initial", current.clone(), JsValue::UNDEFINED);
+        cb.call2(&JsValue::UNDEFINED, &current, &ctx.into())?;
+        Ok(self.subscribe_after(cb, current))
+    }
+
+    /// Like subscribe, but each subscriber is given a deeply frozen structuredClone of the value, so
+    /// that it can't mutate it in place. The observable's own value is left as it is. Exported Rust
+    /// structs are delivered as they are, unfrozen, as are values which can't be cloned, such as
+    /// those holding functions, in which case an error is logged
+    pub fn subscribe_frozen(&mut self, cb: js_sys::Function) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let clean_up = self.obs.subscribe(Box::new(move |v: JsValue| {
+            if is_wasm_bindgen_instance(&v) {
+                return call_subscriber(&cb, &v, type_name);
+            }
+            match structured_clone(&v) {
+                Ok(frozen) => {
+                    deep_freeze(&frozen, &js_sys::Set::new(&JsValue::UNDEFINED));
+                    call_subscriber(&cb, &frozen, type_name)
+                }
+                Err(err) => {
+                    console_error(
+                        &format!("value of {} could not be cloned for freezing", type_name).into(),
+                    );
+                    console_error(&err);
+                    call_subscriber(&cb, &v, type_name)
+                }
+            }
+        }));
+
+        let unsub = Closure::once_into_js(Box::new(move || drop(clean_up)) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Like subscribe, but each value is passed through `transform` before being delivered to `cb`.
+    /// If the transform throws, the error is logged and that change is not delivered
+    pub fn subscribe_mapped(
+        &mut self,
+        transform: js_sys::Function,
+        cb: js_sys::Function,
+    ) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let clean_up = self.obs.subscribe(Box::new(move |v: JsValue| {
+            match transform.call1(&JsValue::UNDEFINED, &v) {
+                Ok(mapped) => call_subscriber(&cb, &mapped, type_name),
+                Err(err) => {
+                    console_error(
+                        &format!("transform for subscriber of {} threw", type_name).into(),
+                    );
+                    console_error(&err);
+                }
+            }
+        }));
+
+        let unsub = Closure::once_into_js(Box::new(move || drop(clean_up)) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Subscribe to the value found at `path`, an array of property names and indexes such as
+    /// `["child", 0, "value"]`. `cb` is called with that nested value, only when it changes (by deep
+    /// equality), so changes elsewhere in the value are ignored. Missing paths yield undefined
+    pub fn subscribe_path(
         &mut self,
+        path: js_sys::Array,
         cb: js_sys::Function,
-        // TODO: ChangeContext contract from TS?
     ) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let at_path = move |value: JsValue| {
+            path.iter().fold(value, |value, key| {
+                if value.is_object() {
+                    js_sys::Reflect::get(&value, &key).unwrap_or(JsValue::UNDEFINED)
+                } else {
+                    JsValue::UNDEFINED
+                }
+            })
+        };
+        let previous = RefCell::new(at_path(self.obs.get_js()));
         let clean_up = self.obs.subscribe(Box::new(move |v: JsValue| {
-            cb.call1(&JsValue::UNDEFINED, &v).unwrap();
+            let current = at_path(v);
+            if !derived::deep_equal(&previous.borrow(), &current) {
+                previous.replace(current.clone());
+                call_subscriber(&cb, &current, type_name);
+            }
         }));
 
         let unsub = Closure::once_into_js(Box::new(move || drop(clean_up)) as Box<dyn FnOnce()>);
@@ -105,10 +421,261 @@ impl JsObservable {
         unsub.into()
     }
 
+    /// Like subscribe, but changes are only delivered when `equals(previous, current)` is falsy,
+    /// `previous` being the last value which was delivered (or the value at the time of subscribing).
+    /// If `equals` throws, the error is logged and the change is delivered
+    pub fn subscribe_distinct_by(
+        &mut self,
+        equals: js_sys::Function,
+        cb: js_sys::Function,
+    ) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let previous = RefCell::new(self.obs.get_js());
+        let clean_up = self.obs.subscribe(Box::new(move |v: JsValue| {
+            let equal = match equals.call2(&JsValue::UNDEFINED, &previous.borrow(), &v) {
+                Ok(equal) => equal.is_truthy(),
+                Err(err) => {
+                    console_error(
+                        &format!("equality for subscriber of {} threw", type_name).into(),
+                    );
+                    console_error(&err);
+                    false
+                }
+            };
+            if !equal {
+                previous.replace(v.clone());
+                call_subscriber(&cb, &v, type_name);
+            }
+        }));
+
+        let unsub = Closure::once_into_js(Box::new(move || drop(clean_up)) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Like subscribe, but also calls `complete` once the underlying Observable is dropped, after which
+    /// there will be no further changes
+    pub fn subscribe2(
+        &mut self,
+        next: js_sys::Function,
+        complete: js_sys::Function,
+    ) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let clean_up = self.obs.subscribe(Box::new(move |v: JsValue| {
+            call_subscriber(&next, &v, type_name);
+        }));
+        let complete_clean_up = self.obs.on_complete(Box::new(move || {
+            call_subscriber(&complete, &JsValue::UNDEFINED, type_name);
+        }));
+
+        let unsub = Closure::once_into_js(Box::new(move || {
+            drop(clean_up);
+            drop(complete_clean_up);
+        }) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Like subscribe, but the subscription is also dropped once `node` is removed from the document,
+    /// so that it does not leak if the unsubscribe function is never called
+    pub fn subscribe_while_mounted(
+        &mut self,
+        cb: js_sys::Function,
+        node: JsValue,
+    ) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let clean_up = self.obs.subscribe(Box::new(move |v: JsValue| {
+            call_subscriber(&cb, &v, type_name);
+        }));
+        let mount = Mount::watch(node, clean_up);
+
+        let unsub = Closure::once_into_js(Box::new(move || mount.release()) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Subscribe right away, but deliver the current value on the next animation frame rather than
+    /// synchronously, so that mounting components can paint first. The initial delivery is skipped
+    /// if a change was already delivered, or if unsubscribed before then
+    pub fn subscribe_after_frame(&mut self, cb: js_sys::Function) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let pending = Rc::new(Cell::new(true));
+
+        let clean_up = self.obs.subscribe(Box::new({
+            let cb = cb.clone();
+            let pending = pending.clone();
+            move |v: JsValue| {
+                pending.set(false);
+                call_subscriber(&cb, &v, type_name);
+            }
+        }));
+
+        let initial = {
+            let obs = dyn_clone::clone_box(&*self.obs);
+            let pending = pending.clone();
+            Box::new(move || {
+                if pending.replace(false) {
+                    call_subscriber(&cb, &obs.get_js(), type_name);
+                }
+            })
+        };
+        scheduler().animation_frame(initial);
+
+        let unsub = Closure::once_into_js(Box::new(move || {
+            pending.set(false);
+            drop(clean_up)
+        }) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Subscribe, delivering the latest value only once there have been no changes for `ms` milliseconds
+    pub fn subscribe_debounced(&mut self, cb: js_sys::Function, ms: i32) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let generation = Rc::new(Cell::new(0usize));
+
+        let clean_up = self.obs.subscribe(Box::new({
+            let obs = dyn_clone::clone_box(&*self.obs);
+            let generation = generation.clone();
+            move |_: JsValue| {
+                let scheduled = generation.get() + 1;
+                generation.set(scheduled);
+
+                let obs = dyn_clone::clone_box(&*obs);
+                let generation = Rc::downgrade(&generation);
+                let cb = cb.clone();
+                scheduler().timeout(
+                    ms,
+                    Box::new(move || {
+                        // Superseded by a later change, or unsubscribed
+                        if generation.upgrade().map(|g| g.get()) == Some(scheduled) {
+                            call_subscriber(&cb, &obs.get_js(), type_name);
+                        }
+                    }),
+                );
+            }
+        }));
+
+        let unsub = Closure::once_into_js(Box::new(move || {
+            drop(generation);
+            drop(clean_up)
+        }) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Subscribe at low priority. Changes are coalesced, and the latest value is delivered once the
+    /// browser is idle, or as soon as it can be where idle callbacks are not supported. See IdleStrategy
+    pub fn subscribe_low_priority(&mut self, cb: js_sys::Function) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let scheduled = Rc::new(Cell::new(false));
+
+        let clean_up = self.obs.subscribe(Box::new({
+            let obs = dyn_clone::clone_box(&*self.obs);
+            let scheduled = scheduled.clone();
+            move |_: JsValue| {
+                if scheduled.replace(true) {
+                    return;
+                }
+                let obs = dyn_clone::clone_box(&*obs);
+                let scheduled = Rc::downgrade(&scheduled);
+                let cb = cb.clone();
+                scheduler().idle(Box::new(move || {
+                    // Unsubscribed in the meantime
+                    let Some(scheduled) = scheduled.upgrade() else {
+                        return;
+                    };
+                    scheduled.set(false);
+                    call_subscriber(&cb, &obs.get_js(), type_name);
+                }));
+            }
+        }));
+
+        let unsub = Closure::once_into_js(Box::new(move || {
+            drop(scheduled);
+            drop(clean_up)
+        }) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Subscribe, but hold back deliveries while the document is hidden. The latest value is then
+    /// delivered once when it becomes visible again
+    pub fn subscribe_when_visible(&mut self, cb: js_sys::Function) -> js_sys::Function {
+        let type_name = self.obs.type_name();
+        let buffered = Rc::new(Cell::new(false));
+
+        let clean_up = self.obs.subscribe(Box::new({
+            let cb = cb.clone();
+            let buffered = buffered.clone();
+            move |v: JsValue| {
+                if is_hidden() {
+                    buffered.set(true);
+                } else {
+                    buffered.set(false);
+                    call_subscriber(&cb, &v, type_name);
+                }
+            }
+        }));
+        let listener = {
+            let obs = dyn_clone::clone_box(&*self.obs);
+            VisibilityListener::new(move || {
+                if buffered.replace(false) {
+                    call_subscriber(&cb, &obs.get_js(), type_name);
+                }
+            })
+        };
+
+        let unsub = Closure::once_into_js(Box::new(move || {
+            drop(listener);
+            drop(clean_up)
+        }) as Box<dyn FnOnce()>);
+
+        unsub.into()
+    }
+
+    /// Re-render a class component whenever this observable changes. Returns the unbind function
+    pub fn bind_component(&self, component: ReactComponent) -> js_sys::Function {
+        let clean_up = self.bind(component);
+        Closure::once_into_js(Box::new(move || drop(clean_up)) as Box<dyn FnOnce()>).into()
+    }
+
+    /// Define a getter called `name` on the computed object, which calls `cb` with the current value
+    /// each time it is read
+    pub fn with_computed(&self, name: &str, cb: js_sys::Function) {
+        let getter = {
+            let obs = dyn_clone::clone_box(&*self.obs);
+            Closure::wrap(
+                Box::new(move || cb.call1(&JsValue::UNDEFINED, &obs.get_js()))
+                    as Box<dyn Fn() -> Result<JsValue, JsValue>>,
+            )
+        };
+
+        let descriptor = js_sys::Object::new();
+        js_sys::Reflect::set(&descriptor, &"get".into(), &getter.into_js_value()).unwrap();
+        js_sys::Reflect::set(&descriptor, &"enumerable".into(), &JsValue::TRUE).unwrap();
+        js_sys::Reflect::set(&descriptor, &"configurable".into(), &JsValue::TRUE).unwrap();
+        js_sys::Object::define_property(&self.computed(), &name.into(), &descriptor);
+    }
+
+    /// The object holding the getters registered with with_computed
+    #[wasm_bindgen(getter)]
+    pub fn computed(&self) -> js_sys::Object {
+        self.computed.get_or_init(js_sys::Object::new).clone()
+    }
+
+    /// Another handle on the same observable, which can be freed independently of this one. Getters
+    /// registered with with_computed are not carried over
+    #[wasm_bindgen(js_name = clone)]
+    pub fn clone_handle(&self) -> JsObservable {
+        JsObservable::new(dyn_clone::clone_box(&*self.obs))
+    }
+
     pub fn destroy(&self) {
         // NOOP. Call the free() method instead
     }
 
+    /// Always matches the value most recently delivered to subscribers, including while they are being notified
     #[wasm_bindgen(getter)]
     pub fn value(&self) -> JsValue {
         self.obs.get_js()
@@ -120,6 +687,13 @@ impl JsObservable {
     }
 }
 
+/// Combine several observables into one, whose value is `combiner(...values)`. It updates whenever
+/// any of the sources changes, and keeps them subscribed for as long as it is alive
+#[wasm_bindgen(js_name = combine)]
+pub fn combine_js(sources: js_sys::Array, combiner: js_sys::Function) -> JsObservable {
+    JsDerived::combine(sources, combiner).into()
+}
+
 impl<O> From<O> for JsObservable
 where
     O: JsObserve + 'static + Sized,
@@ -128,3 +702,32 @@ where
         JsObservable::new(Box::new(obs))
     }
 }
+
+impl<T> From<Reader<List<T>>> for JsObservable
+where
+    T: Into<JsValue> + Clone + 'static,
+{
+    fn from(reader: Reader<List<T>>) -> Self {
+        ListReader::new(reader).into()
+    }
+}
+
+/// The JsObservable keeps the MapReader alive, along with its subscriptions to its sources
+impl<T> From<MapReader<T>> for JsObservable
+where
+    T: Into<JsValue> + Clone + 'static,
+{
+    fn from(reader: MapReader<T>) -> Self {
+        JsObservable::new(Box::new(Rc::new(reader)))
+    }
+}
+
+/// Likewise for a LazyMapReader, which is calculated once JS first reads or subscribes to it
+impl<T> From<LazyMapReader<T>> for JsObservable
+where
+    T: Into<JsValue> + Clone + 'static,
+{
+    fn from(reader: LazyMapReader<T>) -> Self {
+        JsObservable::new(Box::new(Rc::new(reader)))
+    }
+}
@@ -0,0 +1,86 @@
+//! Tie the lifetime of a subscription to a DOM node being mounted
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use js_sys::{Object, Reflect};
+use observable_rs::Subscription;
+use wasm_bindgen::{prelude::*, JsCast};
+
+#[wasm_bindgen]
+extern "C" {
+    type Node;
+
+    #[wasm_bindgen(method, getter, js_name = isConnected)]
+    fn is_connected(this: &Node) -> bool;
+
+    #[wasm_bindgen(method, getter, js_name = ownerDocument)]
+    fn owner_document(this: &Node) -> JsValue;
+
+    type MutationObserver;
+
+    #[wasm_bindgen(constructor)]
+    fn new(cb: &JsValue) -> MutationObserver;
+
+    #[wasm_bindgen(method)]
+    fn observe(this: &MutationObserver, target: &JsValue, options: &JsValue);
+
+    #[wasm_bindgen(method)]
+    fn disconnect(this: &MutationObserver);
+}
+
+/// Holds a subscription for as long as a node stays in the document. A node which is not mounted
+/// yet keeps the subscription until it has been mounted and then removed again
+pub(crate) struct Mount {
+    sub: RefCell<Option<Subscription>>,
+    observer: RefCell<Option<MutationObserver>>,
+    mounted: Cell<bool>,
+}
+
+impl Mount {
+    pub(crate) fn watch(node: JsValue, sub: Option<Subscription>) -> Rc<Mount> {
+        let node: Node = node.unchecked_into();
+        let mount = Rc::new(Mount {
+            sub: RefCell::new(sub),
+            observer: RefCell::new(None),
+            mounted: Cell::new(node.is_connected()),
+        });
+
+        let root = match node.owner_document() {
+            document if document.is_object() => document,
+            // Documents have no owner document
+            _ => (*node).clone(),
+        };
+
+        // The observer callback keeps the mount alive until it is released, which disconnects the observer
+        let callback = {
+            let mount = mount.clone();
+            Closure::wrap(Box::new(move || {
+                if node.is_connected() {
+                    mount.mounted.set(true);
+                } else if mount.mounted.get() {
+                    mount.release();
+                }
+            }) as Box<dyn FnMut()>)
+        };
+        let observer = MutationObserver::new(&callback.into_js_value());
+
+        let options = Object::new();
+        Reflect::set(&options, &"childList".into(), &JsValue::TRUE).unwrap();
+        Reflect::set(&options, &"subtree".into(), &JsValue::TRUE).unwrap();
+        observer.observe(&root, &options);
+
+        mount.observer.replace(Some(observer));
+        mount
+    }
+
+    /// Drop the subscription and stop watching the node
+    pub(crate) fn release(&self) {
+        if let Some(observer) = self.observer.take() {
+            observer.disconnect();
+        }
+        self.sub.take();
+    }
+}
@@ -0,0 +1,123 @@
+//! Live JS views of an observable's value, see JsObservable::as_proxy
+
+use std::rc::Rc;
+
+use js_sys::{Array, Object, Proxy, Reflect};
+use wasm_bindgen::{prelude::*, JsCast};
+
+/// Reads the current value at the root of the proxy
+pub(crate) type Getter = Rc<dyn Fn() -> JsValue>;
+/// Called with the path of the written property (outermost key first) and the written value
+pub(crate) type Setter = Rc<dyn Fn(&[String], JsValue)>;
+
+/// A Proxy for the object found at `path` in the current root value. Every read goes back to the
+/// root, so the proxy never goes stale. Nested objects are proxied in turn, and writes to them are
+/// reported with their full path. Nothing is written to the value itself, that is up to the setter.
+/// Keys and property descriptors are forwarded too, so that spreading, for...in, Object.keys and
+/// JSON.stringify see the current value. Arrays are proxied with an array target, so that they
+/// still count as arrays, as long as the value at `path` was an array when the proxy was made
+pub(crate) fn live_proxy(get: Getter, set: Setter, path: Vec<String>) -> Proxy {
+    let current: Getter = {
+        let get = get.clone();
+        let path = path.clone();
+        Rc::new(move || {
+            path.iter().fold(get(), |value, key| {
+                Reflect::get(&value, &key.into()).unwrap_or(JsValue::UNDEFINED)
+            })
+        })
+    };
+    let target: Object = if Array::is_array(&current()) {
+        Array::new().into()
+    } else {
+        Object::new()
+    };
+    let handler = Object::new();
+
+    let set_trap = {
+        let path = path.clone();
+        let set = set.clone();
+        Closure::wrap(
+            Box::new(move |_target: JsValue, key: JsValue, value: JsValue| {
+                // Symbol keyed writes have no path, and are refused
+                let Some(key) = key.as_string() else {
+                    return false;
+                };
+                let mut path = path.clone();
+                path.push(key);
+                set(&path, value);
+                true
+            }) as Box<dyn Fn(JsValue, JsValue, JsValue) -> bool>,
+        )
+    };
+    Reflect::set(&handler, &"set".into(), &set_trap.into_js_value()).unwrap();
+
+    let has_trap = {
+        let current = current.clone();
+        Closure::wrap(Box::new(move |_target: JsValue, key: JsValue| {
+            let value = current();
+            value.is_object() && Reflect::has(&value, &key).unwrap_or(false)
+        }) as Box<dyn Fn(JsValue, JsValue) -> bool>)
+    };
+    Reflect::set(&handler, &"has".into(), &has_trap.into_js_value()).unwrap();
+
+    let own_keys_trap = {
+        let current = current.clone();
+        Closure::wrap(Box::new(move |_target: JsValue| {
+            let value = current();
+            if value.is_object() {
+                Reflect::own_keys(&value).unwrap_or_default()
+            } else {
+                Array::new()
+            }
+        }) as Box<dyn Fn(JsValue) -> Array>)
+    };
+    Reflect::set(&handler, &"ownKeys".into(), &own_keys_trap.into_js_value()).unwrap();
+
+    // A proxy may only report a property as non-configurable if its target has it that way too,
+    // which only holds for the length of arrays
+    let descriptor_trap = {
+        let current = current.clone();
+        Closure::wrap(Box::new(move |target: Object, key: JsValue| {
+            let value = current();
+            if !value.is_object() {
+                return JsValue::UNDEFINED;
+            }
+            let descriptor =
+                Reflect::get_own_property_descriptor(value.unchecked_ref::<Object>(), &key)
+                    .unwrap_or(JsValue::UNDEFINED);
+            let fixed = Reflect::get_own_property_descriptor(&target, &key)
+                .ok()
+                .filter(|own| own.is_object())
+                .is_some_and(|own| {
+                    !Reflect::get(&own, &"configurable".into())
+                        .unwrap_or(JsValue::TRUE)
+                        .is_truthy()
+                });
+            if descriptor.is_object() && !fixed {
+                Reflect::set(&descriptor, &"configurable".into(), &JsValue::TRUE).unwrap();
+            }
+            descriptor
+        }) as Box<dyn Fn(Object, JsValue) -> JsValue>)
+    };
+    Reflect::set(
+        &handler,
+        &"getOwnPropertyDescriptor".into(),
+        &descriptor_trap.into_js_value(),
+    )
+    .unwrap();
+
+    let get_trap = Closure::wrap(Box::new(move |_target: JsValue, key: JsValue| {
+        let value = Reflect::get(&current(), &key).unwrap_or(JsValue::UNDEFINED);
+        match key.as_string() {
+            Some(key) if value.is_object() && !value.is_function() => {
+                let mut path = path.clone();
+                path.push(key);
+                live_proxy(get.clone(), set.clone(), path).into()
+            }
+            _ => value,
+        }
+    }) as Box<dyn Fn(JsValue, JsValue) -> JsValue>);
+    Reflect::set(&handler, &"get".into(), &get_trap.into_js_value()).unwrap();
+
+    Proxy::new(&target, &handler)
+}
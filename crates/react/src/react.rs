@@ -2,7 +2,18 @@
 
 // use js_sys::Function;
 // use serde::{de::DeserializeOwned, Serialize};
-use wasm_bindgen::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use js_sys::{Function, Object, Reflect};
+use observable_rs::Subscription;
+use wasm_bindgen::{convert::TryFromJsValue, prelude::*, JsCast};
+
+use crate::{
+    binding::Binding, console_error, derived::deep_equal, scheduler::scheduler, JsObservable,
+};
 
 #[wasm_bindgen(module = "react")]
 extern "C" {
@@ -12,11 +23,31 @@ extern "C" {
 
     /// Binding to React.useEffect
     #[wasm_bindgen(js_name = useEffect)]
-    fn js_use_effect(effect: &Closure<dyn FnMut()>, bindings: js_sys::Array) -> js_sys::Array;
+    fn js_use_effect(effect: &JsValue, bindings: js_sys::Array);
+
+    /// Binding to React.useRef
+    #[wasm_bindgen(js_name = useRef)]
+    fn js_use_ref(initial_value: JsValue) -> JsValue;
+
+    /// Binding to React.useDeferredValue
+    #[wasm_bindgen(js_name = useDeferredValue)]
+    fn js_use_deferred_value(value: &JsValue) -> JsValue;
+
+    /// Binding to React.createContext
+    #[wasm_bindgen(js_name = createContext)]
+    fn js_create_context(default_value: &JsValue) -> JsValue;
+
+    /// Binding to React.useContext
+    #[wasm_bindgen(js_name = useContext)]
+    fn js_use_context(context: &JsValue) -> JsValue;
+
+    /// Binding to React.createElement
+    #[wasm_bindgen(js_name = createElement)]
+    fn js_create_element(element_type: &JsValue, props: &JsValue, children: &JsValue) -> JsValue;
 
     /// Binding to React.useReducer
     #[wasm_bindgen(js_name = useReducer)]
-    fn js_use_reducer(reducer: &Closure<dyn FnMut()>, initial_value: JsValue) -> js_sys::Array;
+    fn js_use_reducer(reducer: &Function, initial_value: JsValue) -> js_sys::Array;
 }
 
 // Duck type for React components
@@ -28,6 +59,61 @@ extern "C" {
     pub fn forceUpdate(this: &ReactComponent);
 }
 
+impl Binding for ReactComponent {
+    fn request_update(&self) {
+        self.forceUpdate()
+    }
+}
+
+/// Binds several observables to one class component, coalescing their changes into a single
+/// forceUpdate per microtask rather than one per change
+#[wasm_bindgen]
+pub struct ReactComponentBinder {
+    inner: Rc<BinderInner>,
+}
+
+struct BinderInner {
+    component: ReactComponent,
+    scheduled: Cell<bool>,
+    subs: RefCell<Vec<Subscription>>,
+}
+
+#[wasm_bindgen]
+impl ReactComponentBinder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(component: ReactComponent) -> Self {
+        ReactComponentBinder {
+            inner: Rc::new(BinderInner {
+                component,
+                scheduled: Cell::new(false),
+                subs: RefCell::default(),
+            }),
+        }
+    }
+    /// Update the component when `obs` changes, until unbind_all is called or the binder is freed
+    pub fn bind(&self, obs: &JsObservable) {
+        let inner = Rc::downgrade(&self.inner);
+        let sub = obs.bind(move || {
+            let Some(inner) = inner.upgrade() else { return };
+            if inner.scheduled.replace(true) {
+                return;
+            }
+            let inner = Rc::downgrade(&inner);
+            scheduler().microtask(Box::new(move || {
+                if let Some(inner) = inner.upgrade() {
+                    inner.scheduled.set(false);
+                    inner.component.forceUpdate();
+                }
+            }));
+        });
+        self.inner.subs.borrow_mut().extend(sub);
+    }
+    #[wasm_bindgen(js_name = unbindAll)]
+    pub fn unbind_all(&self) {
+        self.inner.subs.borrow_mut().clear();
+    }
+}
+
 // /// Oxidized interface to React.useState
 // pub fn use_state<T>(initial_value: T) -> (T, impl Fn(T))
 // where
@@ -46,3 +132,121 @@ extern "C" {
 
 //     (current, cb)
 // }
+
+thread_local! {
+    /// The reducer behind use_observable's force update, counting the changes
+    static INCREMENT: Function = Function::new_with_args("n", "return n + 1");
+}
+
+/// Bind an observable to the calling function component, returning its current value. Each render
+/// reads the value with get, and a useEffect subscribes to force a re-render through a useReducer
+/// counter on every change. React never sees the value itself as state, so it can't skip a render
+/// because the value is the same object as before, as with a list which was pushed to. The effect's
+/// dependency array is empty, so the subscription is made once on mount and dropped on unmount. This
+/// means the hook keeps following the observable it was first called with
+pub fn use_observable(obs: &JsObservable) -> JsValue {
+    let reducer = INCREMENT.with(Function::clone);
+    let force_update: Function = js_use_reducer(&reducer, 0.into()).get(1).unchecked_into();
+    let value = obs.get();
+
+    // Build the effect only once, so we don't leave a closure behind on every render
+    let hook_ref = js_use_ref(JsValue::UNDEFINED);
+    Reflect::set(&hook_ref, &"rendered".into(), &value).unwrap();
+    let mut effect = Reflect::get(&hook_ref, &"current".into()).unwrap_or(JsValue::UNDEFINED);
+    if effect.is_undefined() {
+        let obs = dyn_clone::clone_box(&*obs.obs);
+        let refs = hook_ref.clone();
+        effect = Closure::wrap(Box::new(move || {
+            let update = {
+                let force_update = force_update.clone();
+                move || {
+                    if let Err(err) = force_update.call0(&JsValue::UNDEFINED) {
+                        console_error(&err);
+                    }
+                }
+            };
+            let sub = {
+                let update = update.clone();
+                obs.subscribe(Box::new(move |_| update()))
+            };
+            // Catch any change made between the render and this effect. A list hands out a new
+            // array every time, so compare the contents
+            let rendered = Reflect::get(&refs, &"rendered".into()).unwrap_or(JsValue::UNDEFINED);
+            if !deep_equal(&rendered, &obs.get_js()) {
+                update();
+            }
+            Closure::once_into_js(Box::new(move || drop(sub)) as Box<dyn FnOnce()>)
+        }) as Box<dyn FnMut() -> JsValue>)
+        .into_js_value();
+        Reflect::set(&hook_ref, &"current".into(), &effect).unwrap();
+    }
+    js_use_effect(&effect, js_sys::Array::new());
+
+    value
+}
+
+/// Bind an observable to the calling function component through React.useDeferredValue, so that
+/// renders driven by the observable don't block more urgent updates
+pub fn use_deferred_observable(obs: &JsObservable) -> JsValue {
+    let value = use_observable(obs);
+    js_use_deferred_value(&value)
+}
+
+/// Package an observable as a React context, returning `{ Provider, useValue }`. Wrap a subtree in
+/// the Provider, and call the useValue hook from components within it to bind them to the provided
+/// observable. The Provider provides `obs` unless it is given another JsObservable as its `value`
+/// prop. Either way the context holds the JsObservable itself, for JS code which wants to useContext
+/// directly. Outside of a Provider, useValue logs an error and falls back to `obs`
+pub fn create_observable_context(obs: JsObservable) -> JsValue {
+    let context = js_create_context(&JsValue::NULL);
+    let provided: JsValue = obs.clone_handle().into();
+    let obs = Rc::new(obs);
+
+    let provider = {
+        let context = context.clone();
+        Closure::wrap(Box::new(move |props: JsValue| {
+            let children = Reflect::get(&props, &"children".into()).unwrap_or(JsValue::UNDEFINED);
+            let value = Reflect::get(&props, &"value".into())
+                .ok()
+                .filter(|value| !value.is_undefined())
+                .unwrap_or_else(|| provided.clone());
+            let provider_props = Object::new();
+            Reflect::set(&provider_props, &"value".into(), &value).unwrap();
+            let provider = Reflect::get(&context, &"Provider".into()).unwrap();
+            js_create_element(&provider, &provider_props, &children)
+        }) as Box<dyn FnMut(JsValue) -> JsValue>)
+        .into_js_value()
+    };
+
+    let use_value = Closure::wrap(Box::new(move || {
+        let provided = js_use_context(&context);
+        if provided.is_null() {
+            console_error(&"useValue was called outside of its observable context Provider".into());
+            return use_observable(&obs);
+        }
+        match context_observable(&provided) {
+            Some(provided) => use_observable(&provided),
+            None => {
+                console_error(&"The observable context Provider was given a value which is not a JsObservable".into());
+                use_observable(&obs)
+            }
+        }
+    }) as Box<dyn FnMut() -> JsValue>)
+    .into_js_value();
+
+    let result = Object::new();
+    Reflect::set(&result, &"Provider".into(), &provider).unwrap();
+    Reflect::set(&result, &"useValue".into(), &use_value).unwrap();
+    result.into()
+}
+
+/// A handle on the JsObservable held by a context. Converting a JsValue into an exported struct takes
+/// ownership of it, so this converts a clone, leaving the context's own handle usable
+fn context_observable(provided: &JsValue) -> Option<JsObservable> {
+    let clone: Function = Reflect::get(provided, &"clone".into())
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    let handle = clone.call0(provided).ok()?;
+    JsObservable::try_from_js_value(handle).ok()
+}
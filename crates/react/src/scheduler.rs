@@ -0,0 +1,159 @@
+//! Where timer and animation frame based work gets scheduled. Tests can swap in a ManualScheduler
+//! to run that work synchronously
+
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    rc::Rc,
+};
+
+use js_sys::Reflect;
+use wasm_bindgen::prelude::*;
+
+use crate::{queue_microtask, request_animation_frame, request_idle_callback, set_timeout};
+
+pub type Task = Box<dyn FnOnce()>;
+
+pub trait Scheduler {
+    /// Run the task once `ms` milliseconds have passed
+    fn timeout(&self, ms: i32, task: Task);
+    /// Run the task before the next repaint
+    fn animation_frame(&self, task: Task);
+    /// Run low priority work once there is nothing more important to do
+    fn idle(&self, task: Task) {
+        self.timeout(0, task)
+    }
+    /// Run the task once the code which is currently running has finished
+    fn microtask(&self, task: Task) {
+        self.timeout(0, task)
+    }
+    /// The current time in milliseconds, for measuring how long things take
+    fn now(&self) -> f64 {
+        js_sys::Date::now()
+    }
+}
+
+/// The best available way of running low priority work
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleStrategy {
+    IdleCallback,
+    AnimationFrame,
+    Timeout,
+}
+
+thread_local! {
+    static IDLE_STRATEGY: OnceCell<IdleStrategy> = const { OnceCell::new() };
+}
+
+impl IdleStrategy {
+    /// Prefer requestIdleCallback, then requestAnimationFrame, then setTimeout(0), depending on
+    /// what this runtime provides
+    pub fn detect() -> Self {
+        let global = js_sys::global();
+        let supports = |name: &str| {
+            Reflect::get(&global, &name.into())
+                .map(|f| f.is_function())
+                .unwrap_or(false)
+        };
+        if supports("requestIdleCallback") {
+            IdleStrategy::IdleCallback
+        } else if supports("requestAnimationFrame") {
+            IdleStrategy::AnimationFrame
+        } else {
+            IdleStrategy::Timeout
+        }
+    }
+    /// The strategy for this runtime, detected on first use
+    pub fn current() -> Self {
+        IDLE_STRATEGY.with(|strategy| *strategy.get_or_init(Self::detect))
+    }
+}
+
+/// Schedules using setTimeout, requestAnimationFrame, requestIdleCallback and queueMicrotask
+pub struct BrowserScheduler;
+
+impl Scheduler for BrowserScheduler {
+    fn timeout(&self, ms: i32, task: Task) {
+        set_timeout(&Closure::once_into_js(task), ms);
+    }
+    fn animation_frame(&self, task: Task) {
+        request_animation_frame(&Closure::once_into_js(task));
+    }
+    fn idle(&self, task: Task) {
+        match IdleStrategy::current() {
+            IdleStrategy::IdleCallback => {
+                request_idle_callback(&Closure::once_into_js(task));
+            }
+            IdleStrategy::AnimationFrame => self.animation_frame(task),
+            IdleStrategy::Timeout => self.timeout(0, task),
+        }
+    }
+    fn microtask(&self, task: Task) {
+        queue_microtask(&Closure::once_into_js(task));
+    }
+}
+
+/// Queues tasks until flush is called, regardless of their delay. Its clock only moves when advanced
+#[derive(Default)]
+pub struct ManualScheduler {
+    tasks: RefCell<Vec<Task>>,
+    now: Cell<f64>,
+}
+
+impl ManualScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The number of tasks waiting to be run
+    pub fn pending(&self) -> usize {
+        self.tasks.borrow().len()
+    }
+    /// Move the clock forward
+    pub fn advance(&self, ms: f64) {
+        self.now.set(self.now.get() + ms);
+    }
+    /// Run the queued tasks, but not the ones which they schedule in turn. For stepping through
+    /// animation frames one at a time
+    pub fn run_pending(&self) {
+        let tasks = std::mem::take(&mut *self.tasks.borrow_mut());
+        for task in tasks {
+            task();
+        }
+    }
+    /// Run the queued tasks in the order they were scheduled, including any they schedule in turn
+    pub fn flush(&self) {
+        loop {
+            let tasks = std::mem::take(&mut *self.tasks.borrow_mut());
+            if tasks.is_empty() {
+                break;
+            }
+            for task in tasks {
+                task();
+            }
+        }
+    }
+}
+
+impl Scheduler for ManualScheduler {
+    fn timeout(&self, _ms: i32, task: Task) {
+        self.tasks.borrow_mut().push(task)
+    }
+    fn animation_frame(&self, task: Task) {
+        self.tasks.borrow_mut().push(task)
+    }
+    fn now(&self) -> f64 {
+        self.now.get()
+    }
+}
+
+thread_local! {
+    static SCHEDULER: RefCell<Rc<dyn Scheduler>> = RefCell::new(Rc::new(BrowserScheduler));
+}
+
+/// Install the scheduler used by all time based features, returning the previous one
+pub fn set_scheduler(scheduler: Rc<dyn Scheduler>) -> Rc<dyn Scheduler> {
+    SCHEDULER.with(|current| current.replace(scheduler))
+}
+
+pub(crate) fn scheduler() -> Rc<dyn Scheduler> {
+    SCHEDULER.with(|current| current.borrow().clone())
+}
@@ -0,0 +1,85 @@
+//! How values are turned into JsValues. By default that's their Into<JsValue> impl, but a JsObservable
+//! can be given a Serializer instead, for instance a faster one for large payloads
+
+use std::rc::Rc;
+
+use observable_rs::{Reader, Subscription};
+use wasm_bindgen::JsValue;
+
+use crate::traits::JsObserve;
+
+pub trait Serializer<T> {
+    fn serialize(&self, value: &T) -> JsValue;
+}
+
+/// The default, using the value's Into<JsValue> impl
+pub struct IntoJs;
+
+impl<T: Into<JsValue> + Clone> Serializer<T> for IntoJs {
+    fn serialize(&self, value: &T) -> JsValue {
+        value.clone().into()
+    }
+}
+
+/// Converts with the value's Tsify impl, so it matches the declaration derived for it
+#[cfg(feature = "tsify")]
+pub struct TsifyJs;
+
+#[cfg(feature = "tsify")]
+impl<T: tsify::Tsify + serde::Serialize> Serializer<T> for TsifyJs {
+    fn serialize(&self, value: &T) -> JsValue {
+        match value.into_js() {
+            Ok(js) => js.into(),
+            Err(err) => {
+                crate::console_error(&err.into());
+                JsValue::UNDEFINED
+            }
+        }
+    }
+}
+
+/// A Reader whose values are serialized with a chosen Serializer
+pub(crate) struct Serialized<T> {
+    reader: Reader<T>,
+    serializer: Rc<dyn Serializer<T>>,
+}
+
+impl<T> Serialized<T> {
+    pub(crate) fn new(reader: Reader<T>, serializer: Rc<dyn Serializer<T>>) -> Self {
+        Serialized { reader, serializer }
+    }
+}
+
+impl<T> Clone for Serialized<T> {
+    fn clone(&self) -> Self {
+        Serialized {
+            reader: self.reader.clone(),
+            serializer: self.serializer.clone(),
+        }
+    }
+}
+
+impl<T: 'static> JsObserve for Serialized<T> {
+    fn get_js(&self) -> JsValue {
+        self.serializer.serialize(&self.reader.value())
+    }
+
+    fn subscribe(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        let serializer = self.serializer.clone();
+        self.reader
+            .subscribe(move |v: &T| cb(serializer.serialize(v)))
+    }
+
+    fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        let serializer = self.serializer.clone();
+        self.reader.once(move |v: &T| cb(serializer.serialize(v)))
+    }
+
+    fn on_complete(&self, cb: Box<dyn FnOnce()>) -> Option<Subscription> {
+        self.reader.on_complete(cb)
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
@@ -1,12 +1,12 @@
-use std::cell::Ref;
+use std::{cell::Ref, rc::Rc};
 
 use dyn_clone::DynClone;
 use js_sys::Function;
-use observable_rs::{Reader, Subscription};
+use observable_rs::{LazyMapReader, MapReader, Reader, Subscription};
 // use serde::{de::DeserializeOwned, Serialize};
 use wasm_bindgen::JsValue;
 
-use crate::collections::List;
+use crate::collections::{List, ListReader, Map};
 
 // Traits for javascript-specific functionality around Observable<T>
 
@@ -19,16 +19,26 @@ pub trait JsObserve: DynClone {
 
     /// The default implementation of map is to call the closure once
     /// with the output of .get - other types may call the closure multiple
-    /// times for different sub-values
-    fn map_js(&self, cb: Function) -> JsValue {
+    /// times for different sub-values. Whatever the closure throws is returned as the error
+    fn map_js(&self, cb: Function) -> Result<JsValue, JsValue> {
         let ar = js_sys::Array::new();
-        let ret = cb.call1(&JsValue::UNDEFINED, &self.get_js()).unwrap();
+        let ret = cb.call1(&JsValue::UNDEFINED, &self.get_js())?;
         ar.push(&ret);
-        ar.into()
+        Ok(ar.into())
     }
 
     fn subscribe(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription>;
     fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription>;
+
+    /// Fires once there will be no further changes. The default implementation never completes
+    fn on_complete(&self, _cb: Box<dyn FnOnce()>) -> Option<Subscription> {
+        None
+    }
+
+    /// Name of the underlying observable type, used for diagnostics
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 impl<T> JsObserve for Reader<T>
@@ -48,23 +58,99 @@ where
     fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
         self.once(move |v: &T| cb(v.clone().into()))
     }
+
+    fn on_complete(&self, cb: Box<dyn FnOnce()>) -> Option<Subscription> {
+        self.on_complete(cb)
+    }
 }
 
-impl<T: 'static> JsObserve for Reader<List<T>>
+impl<T: 'static> JsObserve for ListReader<T>
 where
     T: Into<JsValue> + Clone,
 {
-    // we need to be able provide a JS value (JS only has one value type)
     fn get_js(&self) -> JsValue {
-        let a: Ref<List<T>> = self.value();
+        let list: Ref<List<T>> = self.reader.value();
+        self.array(&list).into()
+    }
+
+    fn subscribe(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        let this = self.clone();
+        self.reader
+            .subscribe(move |v: &List<T>| cb(this.array(v).into()))
+    }
+
+    fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        let this = self.clone();
+        self.reader
+            .once(move |v: &List<T>| cb(this.array(v).into()))
+    }
+
+    fn on_complete(&self, cb: Box<dyn FnOnce()>) -> Option<Subscription> {
+        self.reader.on_complete(cb)
+    }
+}
+
+impl<K: 'static, V: 'static> JsObserve for Reader<Map<K, V>>
+where
+    K: Into<JsValue> + Clone,
+    V: Into<JsValue> + Clone,
+{
+    fn get_js(&self) -> JsValue {
+        let a: Ref<Map<K, V>> = self.value();
         (&*a).into()
     }
 
     fn subscribe(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
-        self.subscribe(move |v: &List<T>| cb(v.into()))
+        self.subscribe(move |v: &Map<K, V>| cb(v.into()))
+    }
+
+    fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        self.once(move |v: &Map<K, V>| cb(v.into()))
+    }
+
+    fn on_complete(&self, cb: Box<dyn FnOnce()>) -> Option<Subscription> {
+        self.on_complete(cb)
+    }
+}
+
+/// MapReaders own their listener set, so they are shared rather than cloned
+impl<T> JsObserve for Rc<MapReader<T>>
+where
+    T: Into<JsValue> + Clone + 'static,
+{
+    fn get_js(&self) -> JsValue {
+        let a: Ref<T> = self.value();
+        (*a).clone().into()
+    }
+
+    fn subscribe(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        Some(MapReader::subscribe(self, move |v: &T| {
+            cb(v.clone().into())
+        }))
+    }
+
+    fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        Some(MapReader::once(self, move |v: &T| cb(v.clone().into())))
+    }
+}
+
+/// Like a MapReader, calculating the value on first use
+impl<T> JsObserve for Rc<LazyMapReader<T>>
+where
+    T: Into<JsValue> + Clone + 'static,
+{
+    fn get_js(&self) -> JsValue {
+        let a: Ref<T> = self.value();
+        (*a).clone().into()
+    }
+
+    fn subscribe(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
+        Some(LazyMapReader::subscribe(self, move |v: &T| {
+            cb(v.clone().into())
+        }))
     }
 
     fn once(&self, cb: Box<dyn Fn(JsValue)>) -> Option<Subscription> {
-        self.once(move |v: &List<T>| cb(v.into()))
+        Some(LazyMapReader::once(self, move |v: &T| cb(v.clone().into())))
     }
 }
@@ -0,0 +1,62 @@
+//! Follow document.visibilityState, so that work can be paused while the tab is in the background
+
+use js_sys::Reflect;
+use wasm_bindgen::{prelude::*, JsCast};
+
+#[wasm_bindgen]
+extern "C" {
+    type Document;
+
+    #[wasm_bindgen(method, getter)]
+    fn hidden(this: &Document) -> bool;
+
+    #[wasm_bindgen(method, js_name = addEventListener)]
+    fn add_event_listener(this: &Document, event: &str, listener: &JsValue);
+
+    #[wasm_bindgen(method, js_name = removeEventListener)]
+    fn remove_event_listener(this: &Document, event: &str, listener: &JsValue);
+}
+
+/// The global document, looked up each time rather than at startup. There is none outside of the
+/// browser's main thread, as when rendering on the server or in a worker
+fn document() -> Option<Document> {
+    Reflect::get(&js_sys::global(), &"document".into())
+        .ok()
+        .filter(JsValue::is_object)
+        .map(JsCast::unchecked_into)
+}
+
+/// Without a document there is no background tab, so everything counts as visible
+pub(crate) fn is_hidden() -> bool {
+    document().is_some_and(|document| document.hidden())
+}
+
+/// Calls `on_visible` each time the document becomes visible again, until dropped
+pub(crate) struct VisibilityListener {
+    document: Document,
+    listener: Closure<dyn FnMut()>,
+}
+
+impl VisibilityListener {
+    /// None if there is no document, which then never becomes hidden, so there is nothing to listen to
+    pub(crate) fn new(on_visible: impl Fn() + 'static) -> Option<Self> {
+        let document = document()?;
+        let listener = {
+            let document: Document = (*document).clone().unchecked_into();
+            Closure::wrap(Box::new(move || {
+                if !document.hidden() {
+                    on_visible()
+                }
+            }) as Box<dyn FnMut()>)
+        };
+        document.add_event_listener("visibilitychange", listener.as_ref());
+        Some(VisibilityListener { document, listener })
+    }
+}
+
+impl Drop for VisibilityListener {
+    fn drop(&mut self) {
+        self.document
+            .remove_event_listener("visibilitychange", self.listener.as_ref());
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+use observable_rs::{Observable, Reader};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{console_error, scheduler::scheduler};
+
+#[wasm_bindgen]
+extern "C" {
+    type WebSocket;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(url: &str) -> Result<WebSocket, JsValue>;
+
+    #[wasm_bindgen(method, setter)]
+    fn set_onopen(this: &WebSocket, cb: Option<&js_sys::Function>);
+    #[wasm_bindgen(method, setter)]
+    fn set_onmessage(this: &WebSocket, cb: Option<&js_sys::Function>);
+    #[wasm_bindgen(method, setter)]
+    fn set_onclose(this: &WebSocket, cb: Option<&js_sys::Function>);
+
+    #[wasm_bindgen(method)]
+    fn close(this: &WebSocket);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Open,
+    Closed,
+}
+
+impl From<ConnectionState> for JsValue {
+    fn from(state: ConnectionState) -> Self {
+        match state {
+            ConnectionState::Connecting => "connecting".into(),
+            ConnectionState::Open => "open".into(),
+            ConnectionState::Closed => "closed".into(),
+        }
+    }
+}
+
+/// An Observable which is set from the messages received on a WebSocket. If the connection closes,
+/// it is reopened after `RECONNECT_DELAY_MS`. Dropping this closes the socket for good
+pub struct WebSocketObservable<T>(Rc<Connection<T>>);
+
+pub const RECONNECT_DELAY_MS: i32 = 1000;
+
+type Handler = Closure<dyn FnMut(JsValue)>;
+
+struct Connection<T> {
+    url: String,
+    parse: Box<dyn Fn(JsValue) -> T>,
+    value: Observable<T>,
+    state: Observable<ConnectionState>,
+    socket: RefCell<Option<WebSocket>>,
+    handlers: RefCell<Vec<Handler>>,
+    dropped: Cell<bool>,
+}
+
+impl<T: 'static> WebSocketObservable<T> {
+    /// Start from `initial`, until the first message arrives, then `parse` the data of each message
+    /// into the new value
+    pub fn connect(url: &str, initial: T, parse: impl Fn(JsValue) -> T + 'static) -> Self {
+        let connection = Rc::new(Connection {
+            url: url.to_string(),
+            parse: Box::new(parse),
+            value: Observable::new(initial),
+            state: Observable::new(ConnectionState::Connecting),
+            socket: RefCell::new(None),
+            handlers: RefCell::new(Vec::new()),
+            dropped: Cell::new(false),
+        });
+        Connection::open(&connection);
+        WebSocketObservable(connection)
+    }
+}
+
+impl<T> WebSocketObservable<T> {
+    pub fn reader(&self) -> Reader<T> {
+        self.0.value.reader()
+    }
+    pub fn state(&self) -> Reader<ConnectionState> {
+        self.0.state.reader()
+    }
+}
+
+impl<T: 'static> Connection<T> {
+    fn open(connection: &Rc<Connection<T>>) {
+        connection.state.set(ConnectionState::Connecting);
+
+        let socket = match WebSocket::new(&connection.url) {
+            Ok(socket) => socket,
+            Err(err) => {
+                console_error(&err);
+                Self::closed(connection);
+                return;
+            }
+        };
+
+        // The handlers only hold a weak reference, so that dropping the WebSocketObservable tears everything down
+        let handler = |f: fn(&Rc<Connection<T>>, JsValue)| {
+            let weak: Weak<Connection<T>> = Rc::downgrade(connection);
+            Handler::wrap(Box::new(move |event: JsValue| {
+                if let Some(connection) = weak.upgrade() {
+                    f(&connection, event)
+                }
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        let onopen = handler(|connection, _| connection.state.set(ConnectionState::Open));
+        let onmessage = handler(|connection, event| {
+            let data = js_sys::Reflect::get(&event, &"data".into()).unwrap_or(JsValue::UNDEFINED);
+            connection.value.set((connection.parse)(data));
+        });
+        let onclose = handler(|connection, _| Self::closed(connection));
+
+        socket.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+
+        connection.socket.replace(Some(socket));
+        connection
+            .handlers
+            .replace(vec![onopen, onmessage, onclose]);
+    }
+
+    fn closed(connection: &Rc<Connection<T>>) {
+        connection.state.set(ConnectionState::Closed);
+        if connection.dropped.get() {
+            return;
+        }
+
+        let weak = Rc::downgrade(connection);
+        scheduler().timeout(
+            RECONNECT_DELAY_MS,
+            Box::new(move || {
+                if let Some(connection) = weak.upgrade() {
+                    Self::open(&connection)
+                }
+            }),
+        );
+    }
+}
+
+impl<T> Drop for WebSocketObservable<T> {
+    fn drop(&mut self) {
+        self.0.dropped.set(true);
+        if let Some(socket) = self.0.socket.take() {
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            socket.close();
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Checks the TS declarations wasm-bindgen emits for the typed_observable example. Needs the
+//! wasm32-unknown-unknown target and a wasm-bindgen CLI matching the wasm-bindgen crate
+#![cfg(all(feature = "tsify", not(target_arch = "wasm32")))]
+
+use std::{path::Path, process::Command};
+
+#[test]
+fn tsify_types_flow_into_the_declarations() {
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("declarations");
+    // A target dir of its own, as the one running this test is locked
+    let status = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".into()))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([
+            "build",
+            "--example",
+            "typed_observable",
+            "--features",
+            "tsify",
+        ])
+        .args(["--target", "wasm32-unknown-unknown", "--target-dir"])
+        .arg(out.join("target"))
+        .status()
+        .unwrap();
+    assert!(status.success(), "building the example failed");
+
+    let wasm = out.join("target/wasm32-unknown-unknown/debug/examples/typed_observable.wasm");
+    let status = Command::new("wasm-bindgen")
+        .args(["--target", "nodejs", "--out-dir"])
+        .arg(out.join("pkg"))
+        .arg(wasm)
+        .status()
+        .expect("wasm-bindgen CLI not found");
+    assert!(status.success(), "wasm-bindgen failed");
+
+    let dts = std::fs::read_to_string(out.join("pkg/typed_observable.d.ts")).unwrap();
+    assert!(dts.contains("export interface CatState {"), "{}", dts);
+    assert!(dts.contains("names: string[];"), "{}", dts);
+    assert!(
+        dts.contains("export interface TypedObservable<T> extends JsObservable {"),
+        "{}",
+        dts
+    );
+    assert!(
+        dts.contains("subscribe(cb: Subscriber<T>): () => void;"),
+        "{}",
+        dts
+    );
+    assert!(
+        dts.contains("export function catState(): TypedObservable<CatState>;"),
+        "{}",
+        dts
+    );
+}
@@ -0,0 +1,142 @@
+// A minimal stand-in for React's hooks, enough to drive the hook bindings from tests.
+// Components render synchronously, and state updates re-render immediately. Like React, an update
+// to a value which is Object.is the current one is skipped.
+let current = null;
+const deferred = [];
+
+// Child elements are mounted on the first render only, inheriting the context values provided above them
+function render(component, contexts = new Map()) {
+  const instance = { component, contexts, hooks: [], effects: [], cleanups: [], output: undefined };
+  rerender(instance);
+  instance.children = mount(instance.output, contexts);
+  return instance;
+}
+
+function mount(element, contexts) {
+  if (Array.isArray(element)) return element.flatMap((child) => mount(child, contexts));
+  if (!element || typeof element !== "object" || !("type" in element)) return [];
+  const { type, props } = element;
+  if (type && type._context) {
+    const inner = new Map(contexts);
+    inner.set(type._context, props.value);
+    return mount(props.children, inner);
+  }
+  if (typeof type === "function") return [render(() => type(props), contexts)];
+  return mount(props.children, contexts);
+}
+
+function createElement(type, props, ...children) {
+  return { type, props: { ...props, children: children.length === 1 ? children[0] : children } };
+}
+
+function createContext(defaultValue) {
+  const context = { _default: defaultValue };
+  context.Provider = { _context: context };
+  return context;
+}
+
+function useContext(context) {
+  const contexts = current.contexts;
+  return contexts.has(context) ? contexts.get(context) : context._default;
+}
+
+function rerender(instance) {
+  if (instance.unmounted) return;
+  current = instance;
+  instance.index = 0;
+  try {
+    instance.output = instance.component();
+  } finally {
+    current = null;
+  }
+  const effects = instance.effects;
+  instance.effects = [];
+  for (const [index, effect] of effects) {
+    const cleanup = instance.cleanups[index];
+    if (typeof cleanup === "function") cleanup();
+    instance.cleanups[index] = effect();
+  }
+}
+
+function unmount(instance) {
+  instance.unmounted = true;
+  for (const child of instance.children || []) unmount(child);
+  for (const cleanup of instance.cleanups) {
+    if (typeof cleanup === "function") cleanup();
+  }
+}
+
+function hook(init) {
+  const instance = current;
+  const index = instance.index++;
+  if (!(index in instance.hooks)) instance.hooks[index] = init();
+  return [instance, index];
+}
+
+function useState(initial) {
+  const [instance, index] = hook(() => initial);
+  const setState = (value) => {
+    if (Object.is(instance.hooks[index], value)) return;
+    instance.hooks[index] = value;
+    rerender(instance);
+  };
+  return [instance.hooks[index], setState];
+}
+
+function useReducer(reducer, initial) {
+  const [instance, index] = hook(() => initial);
+  const dispatch = (action) => {
+    const next = reducer(instance.hooks[index], action);
+    if (Object.is(instance.hooks[index], next)) return;
+    instance.hooks[index] = next;
+    rerender(instance);
+  };
+  return [instance.hooks[index], dispatch];
+}
+
+function useRef(initial) {
+  const [instance, index] = hook(() => ({ current: initial }));
+  return instance.hooks[index];
+}
+
+function useEffect(effect, deps) {
+  const [instance, index] = hook(() => ({ deps: undefined }));
+  const previous = instance.hooks[index].deps;
+  const changed =
+    !deps || !previous || deps.length !== previous.length || deps.some((d, i) => !Object.is(d, previous[i]));
+  if (changed) {
+    instance.hooks[index].deps = deps;
+    instance.effects.push([index, effect]);
+  }
+}
+
+// Returns the previous value until flushDeferred() is called, like a low priority re-render would
+function useDeferredValue(value) {
+  const [instance, index] = hook(() => ({ value }));
+  const slot = instance.hooks[index];
+  if (!Object.is(slot.value, value)) {
+    deferred.push(() => {
+      slot.value = value;
+      rerender(instance);
+    });
+  }
+  return slot.value;
+}
+
+function flushDeferred() {
+  for (const update of deferred.splice(0)) update();
+}
+
+module.exports = {
+  render,
+  createElement,
+  createContext,
+  useContext,
+  unmount,
+  flushDeferred,
+  useState,
+  useReducer,
+  useRef,
+  useEffect,
+  useDeferredValue,
+};
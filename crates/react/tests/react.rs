@@ -0,0 +1,158 @@
+//! Tests for the React hook bindings, run against the minimal React mock in tests/mock.
+//! Run with `NODE_PATH=tests/mock wasm-pack test --node`
+
+#![cfg(target_arch = "wasm32")]
+
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Array, Function, Reflect};
+use observable_react::{
+    collections::List,
+    react::{create_observable_context, use_deferred_observable, use_observable},
+    JsObservable,
+};
+use observable_rs::Observable;
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_test::*;
+
+#[wasm_bindgen(module = "react")]
+extern "C" {
+    fn render(component: &Function) -> JsValue;
+    #[wasm_bindgen(js_name = createElement)]
+    fn create_element(element_type: &JsValue, props: &JsValue, children: &JsValue) -> JsValue;
+    fn unmount(instance: &JsValue);
+    #[wasm_bindgen(js_name = flushDeferred)]
+    fn flush_deferred();
+}
+
+/// Wraps a rust closure as a component function, and collects what it returns on each render
+fn component(mut f: impl FnMut() -> JsValue + 'static) -> (Function, Rc<RefCell<Vec<JsValue>>>) {
+    let renders: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let closure = {
+        let renders = renders.clone();
+        Closure::wrap(Box::new(move || {
+            let output = f();
+            renders.borrow_mut().push(output.clone());
+            output
+        }) as Box<dyn FnMut() -> JsValue>)
+    };
+    (closure.into_js_value().unchecked_into(), renders)
+}
+
+#[wasm_bindgen_test]
+fn use_observable_subscribes_until_unmounted() {
+    let obs = Observable::new(1u32);
+    let js_obs: JsObservable = obs.reader().into();
+
+    let (app, renders) = component(move || use_observable(&js_obs));
+    let instance = render(&app);
+    assert_eq!(renders.borrow().last().unwrap(), &1);
+    assert_eq!(obs.listener_count(), 1);
+
+    obs.set(2);
+    assert_eq!(renders.borrow().last().unwrap(), &2);
+
+    unmount(&instance);
+    assert_eq!(obs.listener_count(), 0);
+    let count = renders.borrow().len();
+    obs.set(3);
+    assert_eq!(renders.borrow().len(), count);
+}
+
+#[wasm_bindgen_test]
+fn use_observable_rerenders_on_push() {
+    let obs: Observable<List<u32>> = Observable::new(vec![1].into());
+    let js_obs: JsObservable = obs.reader().into();
+
+    let (app, renders) = component(move || use_observable(&js_obs));
+    let instance = render(&app);
+    assert_eq!(renders.borrow().len(), 1);
+
+    obs.push(2);
+    assert_eq!(renders.borrow().len(), 2);
+    let rendered = Array::from(renders.borrow().last().unwrap());
+    assert_eq!(rendered.to_vec(), vec![1, 2]);
+
+    unmount(&instance);
+}
+
+#[wasm_bindgen_test]
+fn deferred_observable_catches_up() {
+    let obs = Observable::new(1u32);
+    let js_obs: JsObservable = obs.reader().into();
+
+    let (app, renders) = component(move || use_deferred_observable(&js_obs));
+    let instance = render(&app);
+    assert_eq!(renders.borrow().last().unwrap(), &1);
+
+    obs.set(2);
+    assert_eq!(renders.borrow().last().unwrap(), &1);
+
+    flush_deferred();
+    assert_eq!(renders.borrow().last().unwrap(), &2);
+
+    // No more renders once unmounted
+    unmount(&instance);
+    let count = renders.borrow().len();
+    obs.set(3);
+    flush_deferred();
+    assert_eq!(renders.borrow().len(), count);
+}
+
+#[wasm_bindgen_test]
+fn observable_context_provides_value() {
+    let obs = Observable::new(1u32);
+    let context = create_observable_context(obs.reader().into());
+    let provider = Reflect::get(&context, &"Provider".into()).unwrap();
+    let use_value: Function = Reflect::get(&context, &"useValue".into())
+        .unwrap()
+        .unchecked_into();
+
+    let (consumer, renders) = component(move || use_value.call0(&JsValue::UNDEFINED).unwrap());
+    let (app, _) = component(move || {
+        let child = create_element(&consumer, &JsValue::NULL, &JsValue::UNDEFINED);
+        create_element(&provider, &JsValue::NULL, &child)
+    });
+    let instance = render(&app);
+    assert_eq!(renders.borrow().last().unwrap(), &1);
+
+    obs.set(2);
+    assert_eq!(renders.borrow().last().unwrap(), &2);
+
+    unmount(&instance);
+    let count = renders.borrow().len();
+    obs.set(3);
+    assert_eq!(renders.borrow().len(), count);
+}
+
+#[wasm_bindgen_test]
+fn observable_context_uses_provided_observable() {
+    let fallback = Observable::new(1u32);
+    let obs = Observable::new(10u32);
+    let context = create_observable_context(fallback.reader().into());
+    let provider = Reflect::get(&context, &"Provider".into()).unwrap();
+    let use_value: Function = Reflect::get(&context, &"useValue".into())
+        .unwrap()
+        .unchecked_into();
+
+    let (consumer, renders) = component(move || use_value.call0(&JsValue::UNDEFINED).unwrap());
+    let provided: JsValue = JsObservable::from(obs.reader()).into();
+    let (app, _) = component(move || {
+        let props = js_sys::Object::new();
+        Reflect::set(&props, &"value".into(), &provided).unwrap();
+        let child = create_element(&consumer, &JsValue::NULL, &JsValue::UNDEFINED);
+        create_element(&provider, &props, &child)
+    });
+    let instance = render(&app);
+    assert_eq!(renders.borrow().last().unwrap(), &10);
+
+    obs.set(11);
+    assert_eq!(renders.borrow().last().unwrap(), &11);
+    let count = renders.borrow().len();
+    fallback.set(2);
+    assert_eq!(renders.borrow().len(), count);
+
+    unmount(&instance);
+    obs.set(12);
+    assert_eq!(renders.borrow().len(), count);
+}
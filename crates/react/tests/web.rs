@@ -0,0 +1,1286 @@
+//! Test suite for the JS bindings. Run with `wasm-pack test --node`
+
+#![cfg(target_arch = "wasm32")]
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use js_sys::{Array, Function, Reflect};
+use observable_react::{
+    binding::Binding,
+    collections::{List, ListChange, Map, ObservableList, ObservableMap},
+    react::{ReactComponent, ReactComponentBinder},
+    scheduler::{set_scheduler, IdleStrategy, ManualScheduler},
+    websocket::{ConnectionState, WebSocketObservable},
+    JsObservable,
+};
+use observable_rs::{batch_scope, map_obs, map_obs_lazy, Observable};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_test::*;
+
+/// Wraps a rust closure as a JS function, leaking the closure for the duration of the test
+fn js_fn(f: impl FnMut(JsValue) + 'static) -> Function {
+    let closure = Closure::wrap(Box::new(f) as Box<dyn FnMut(JsValue)>);
+    let function: Function = closure.as_ref().unchecked_ref::<Function>().clone();
+    closure.forget();
+    function
+}
+
+/// Replaces a global for as long as it is alive, restoring the original when dropped, even if
+/// the test fails. A global which didn't exist before is deleted again
+struct PatchedGlobal {
+    name: &'static str,
+    original: Option<JsValue>,
+}
+
+impl PatchedGlobal {
+    fn new(name: &'static str, value: &JsValue) -> Self {
+        let global = js_sys::global();
+        let original = Reflect::has(&global, &name.into())
+            .unwrap()
+            .then(|| Reflect::get(&global, &name.into()).unwrap());
+        Reflect::set(&global, &name.into(), value).unwrap();
+        PatchedGlobal { name, original }
+    }
+}
+
+impl Drop for PatchedGlobal {
+    fn drop(&mut self) {
+        let global = js_sys::global();
+        match &self.original {
+            Some(original) => Reflect::set(&global, &self.name.into(), original).unwrap(),
+            None => Reflect::delete_property(&global, &self.name.into()).unwrap(),
+        };
+    }
+}
+
+#[wasm_bindgen_test]
+fn throwing_subscriber_does_not_block_others() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let calls: Rc<Cell<usize>> = Rc::default();
+    let _unsub1 = js_obs.subscribe(Function::new_with_args("v", "throw new Error('boom')"));
+    let _unsub2 = js_obs.subscribe({
+        let calls = calls.clone();
+        js_fn(move |_| calls.set(calls.get() + 1))
+    });
+
+    obs.set(2);
+    assert_eq!(calls.get(), 1);
+
+    obs.set(3);
+    assert_eq!(calls.get(), 2);
+}
+
+#[wasm_bindgen_test]
+fn derived_observable_follows_source() {
+    let obs = Observable::new(1u32);
+    let js_obs: JsObservable = obs.reader().into();
+
+    let mut derived = js_obs.derive(Function::new_with_args("v", "return v * 2"));
+    assert_eq!(derived.get(), 2);
+
+    let last: Rc<Cell<Option<f64>>> = Rc::default();
+    let _unsub = derived.subscribe({
+        let last = last.clone();
+        js_fn(move |v| last.set(v.as_f64()))
+    });
+
+    obs.set(5);
+    assert_eq!(derived.get(), 10);
+    assert_eq!(last.get(), Some(10.0));
+}
+
+/// Replace requestAnimationFrame with a shim which queues callbacks until flushed
+fn shim_raf() -> Array {
+    let queue = Array::new();
+    let shim = Function::new_with_args("cb", "this.push(cb)").bind0(&queue);
+    Reflect::set(&js_sys::global(), &"requestAnimationFrame".into(), &shim).unwrap();
+    queue
+}
+
+fn flush_queue(queue: &Array) {
+    let pending: Vec<JsValue> = queue.iter().collect();
+    queue.set_length(0);
+    for cb in pending {
+        cb.unchecked_into::<Function>()
+            .call0(&JsValue::UNDEFINED)
+            .unwrap();
+    }
+}
+
+#[wasm_bindgen_test]
+fn subscribe_after_frame_delivers_initial_on_frame() {
+    let raf = shim_raf();
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let received: Rc<RefCell<Vec<f64>>> = Rc::default();
+    let _unsub = js_obs.subscribe_after_frame({
+        let received = received.clone();
+        js_fn(move |v| received.borrow_mut().push(v.as_f64().unwrap()))
+    });
+    assert_eq!(*received.borrow(), Vec::<f64>::new());
+
+    flush_queue(&raf);
+    assert_eq!(*received.borrow(), vec![1.0]);
+
+    obs.set(2);
+    flush_queue(&raf);
+    assert_eq!(*received.borrow(), vec![1.0, 2.0]);
+}
+
+/// Install a WebSocket mock which records every instance it creates, and a setTimeout
+/// shim which queues callbacks. Both are restored once the returned guards are dropped
+fn mock_websocket() -> (Array, Array, [PatchedGlobal; 2]) {
+    let sockets = Array::new();
+    let mock = Function::new_with_args(
+        "sockets",
+        "return class { constructor(url) { this.url = url; sockets.push(this) } close() { this.closed = true } }",
+    )
+    .call1(&JsValue::UNDEFINED, &sockets)
+    .unwrap();
+    let websocket = PatchedGlobal::new("WebSocket", &mock);
+
+    let timeouts = Array::new();
+    let shim = Function::new_with_args("cb", "this.push(cb)").bind0(&timeouts);
+    let set_timeout = PatchedGlobal::new("setTimeout", &shim);
+
+    (sockets, timeouts, [websocket, set_timeout])
+}
+
+fn fire(socket: &JsValue, handler: &str, event: &JsValue) {
+    Reflect::get(socket, &handler.into())
+        .unwrap()
+        .unchecked_into::<Function>()
+        .call1(socket, event)
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+fn websocket_observable_receives_and_reconnects() {
+    let (sockets, timeouts, _patched) = mock_websocket();
+
+    let ws = WebSocketObservable::connect("ws://test", 0, |data| {
+        data.as_string().unwrap().parse::<u32>().unwrap()
+    });
+    assert_eq!(sockets.length(), 1);
+    assert_eq!(*ws.state().value(), ConnectionState::Connecting);
+
+    let socket = sockets.get(0);
+    fire(&socket, "onopen", &JsValue::UNDEFINED);
+    assert_eq!(*ws.state().value(), ConnectionState::Open);
+
+    let message = js_sys::Object::new();
+    Reflect::set(&message, &"data".into(), &"5".into()).unwrap();
+    fire(&socket, "onmessage", &message);
+    assert_eq!(*ws.reader().value(), 5);
+
+    // A dropped connection is reopened once the reconnect timeout fires
+    fire(&socket, "onclose", &JsValue::UNDEFINED);
+    assert_eq!(*ws.state().value(), ConnectionState::Closed);
+    flush_queue(&timeouts);
+    assert_eq!(sockets.length(), 2);
+    assert_eq!(*ws.state().value(), ConnectionState::Connecting);
+
+    let reader = ws.reader();
+    drop(ws);
+    assert_eq!(
+        Reflect::get(&sockets.get(1), &"closed".into()).unwrap(),
+        true
+    );
+    assert_eq!(*reader.value(), 5);
+}
+
+#[wasm_bindgen_test]
+fn value_getter_matches_delivered_value() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let delivered: Rc<RefCell<Vec<(JsValue, JsValue)>>> = Rc::default();
+    let _unsub = js_obs.subscribe({
+        let delivered = delivered.clone();
+        let probe: JsObservable = obs.reader().into();
+        js_fn(move |v| delivered.borrow_mut().push((v, probe.value())))
+    });
+    assert_eq!(js_obs.value(), 1);
+
+    obs.set(2);
+    obs.set(3);
+    for (v, during) in delivered.borrow().iter() {
+        assert_eq!(v, during);
+    }
+    assert_eq!(delivered.borrow().last().unwrap().0, js_obs.value());
+    assert_eq!(js_obs.value(), 3);
+}
+
+#[wasm_bindgen_test]
+fn select_ignores_unrelated_changes() {
+    let state = |json: &str| js_sys::JSON::parse(json).unwrap();
+    let obs = Observable::new(state(r#"{"counter": 1, "list": []}"#));
+    let js_obs: JsObservable = obs.reader().into();
+
+    let mut counter = js_obs.select(Function::new_with_args(
+        "s",
+        "return { counter: s.counter }",
+    ));
+    let calls: Rc<Cell<usize>> = Rc::default();
+    let _unsub = counter.subscribe({
+        let calls = calls.clone();
+        js_fn(move |_| calls.set(calls.get() + 1))
+    });
+
+    obs.set(state(r#"{"counter": 1, "list": ["a"]}"#));
+    assert_eq!(calls.get(), 0);
+
+    obs.set(state(r#"{"counter": 2, "list": ["a"]}"#));
+    assert_eq!(calls.get(), 1);
+    assert_eq!(
+        Reflect::get(&counter.value(), &"counter".into()).unwrap(),
+        2
+    );
+}
+
+#[wasm_bindgen_test]
+fn deep_equal_handles_cycles() {
+    use observable_react::derived::deep_equal;
+
+    let cyclic = Function::new_with_args(
+        "n",
+        "const node = { n, children: [] }; node.children.push(node); node.self = node; return node",
+    );
+    let make = |n: u32| cyclic.call1(&JsValue::UNDEFINED, &n.into()).unwrap();
+    assert!(deep_equal(&make(1), &make(1)));
+    assert!(!deep_equal(&make(1), &make(2)));
+}
+
+struct MockBinding(Rc<Cell<usize>>);
+impl Binding for MockBinding {
+    fn request_update(&self) {
+        self.0.set(self.0.get() + 1)
+    }
+}
+
+#[wasm_bindgen_test]
+fn binding_requests_update_on_change() {
+    let obs = Observable::new(1u32);
+    let js_obs: JsObservable = obs.reader().into();
+
+    let updates: Rc<Cell<usize>> = Rc::default();
+    let sub = js_obs.bind(MockBinding(updates.clone()));
+    obs.set(2);
+    assert_eq!(updates.get(), 1);
+
+    drop(sub);
+    obs.set(3);
+    assert_eq!(updates.get(), 1);
+}
+
+#[wasm_bindgen_test]
+fn bind_component_calls_force_update() {
+    let obs = Observable::new(1u32);
+    let js_obs: JsObservable = obs.reader().into();
+
+    let component =
+        Function::new_no_args("return { updates: 0, forceUpdate() { this.updates++ } }")
+            .call0(&JsValue::UNDEFINED)
+            .unwrap();
+    let unbind = js_obs.bind_component(component.clone().unchecked_into::<ReactComponent>());
+
+    obs.set(2);
+    assert_eq!(Reflect::get(&component, &"updates".into()).unwrap(), 1);
+
+    unbind.call0(&JsValue::UNDEFINED).unwrap();
+    obs.set(3);
+    assert_eq!(Reflect::get(&component, &"updates".into()).unwrap(), 1);
+}
+
+#[wasm_bindgen_test]
+fn subscribe2_completes_when_writer_drops() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let nexts: Rc<Cell<usize>> = Rc::default();
+    let completes: Rc<Cell<usize>> = Rc::default();
+    let _unsub = js_obs.subscribe2(
+        {
+            let nexts = nexts.clone();
+            js_fn(move |_| nexts.set(nexts.get() + 1))
+        },
+        {
+            let completes = completes.clone();
+            js_fn(move |_| completes.set(completes.get() + 1))
+        },
+    );
+
+    obs.set(2);
+    assert_eq!((nexts.get(), completes.get()), (1, 0));
+
+    drop(obs);
+    assert_eq!((nexts.get(), completes.get()), (1, 1));
+    assert_eq!(js_obs.value(), 2);
+}
+
+/// Replace MutationObserver with a mock, returning the list of observers created
+fn mock_mutation_observer() -> Array {
+    Function::new_no_args(
+        "globalThis.__observers = [];
+        globalThis.MutationObserver = class {
+            constructor(cb) { this.cb = cb; this.connected = false; globalThis.__observers.push(this); }
+            observe(target, options) { this.target = target; this.connected = true; }
+            disconnect() { this.connected = false; }
+        };
+        return globalThis.__observers;",
+    )
+    .call0(&JsValue::UNDEFINED)
+    .unwrap()
+    .unchecked_into()
+}
+
+#[wasm_bindgen_test]
+fn subscribe_while_mounted_drops_on_removal() {
+    let observers = mock_mutation_observer();
+    let node = js_sys::Object::new();
+    Reflect::set(&node, &"ownerDocument".into(), &js_sys::Object::new()).unwrap();
+    Reflect::set(&node, &"isConnected".into(), &JsValue::TRUE).unwrap();
+
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+    let calls: Rc<Cell<usize>> = Rc::default();
+    let _unsub = {
+        let calls = calls.clone();
+        js_obs.subscribe_while_mounted(
+            js_fn(move |_| calls.set(calls.get() + 1)),
+            node.clone().into(),
+        )
+    };
+
+    let observer = observers.get(0);
+    let mutate = || {
+        let cb: Function = Reflect::get(&observer, &"cb".into())
+            .unwrap()
+            .unchecked_into();
+        cb.call1(&JsValue::UNDEFINED, &Array::new()).unwrap();
+    };
+
+    // Unrelated mutations while mounted leave the subscription alone
+    mutate();
+    obs.set(2);
+    assert_eq!(calls.get(), 1);
+
+    Reflect::set(&node, &"isConnected".into(), &JsValue::FALSE).unwrap();
+    mutate();
+    obs.set(3);
+    assert_eq!(calls.get(), 1);
+    assert_eq!(
+        Reflect::get(&observer, &"connected".into()).unwrap(),
+        JsValue::FALSE
+    );
+}
+
+#[wasm_bindgen_test]
+fn appends_deliver_a_fresh_array() {
+    let obs: Observable<List<u32>> = Observable::new(vec![1].into());
+    let mut js_obs: JsObservable = obs.reader().into();
+    let delivered = Array::new();
+    let _unsub = js_obs.subscribe({
+        let delivered = delivered.clone();
+        js_fn(move |v| {
+            delivered.push(&v);
+        })
+    });
+
+    let first = js_obs.get();
+    obs.push(2);
+    obs.push(3);
+    let appended = js_obs.get();
+    assert!(!js_sys::Object::is(&first, &appended));
+    assert_eq!(Array::from(&first).to_vec(), vec![1]);
+    assert_eq!(Array::from(&appended).to_vec(), vec![1, 2, 3]);
+
+    // Each subscriber call gets its own array too, left as it was by later pushes
+    assert!(!js_sys::Object::is(&delivered.get(0), &delivered.get(1)));
+    assert_eq!(Array::from(&delivered.get(0)).to_vec(), vec![1, 2]);
+    assert_eq!(Array::from(&delivered.get(1)).to_vec(), vec![1, 2, 3]);
+
+    obs.set(vec![4].into());
+    let replaced = js_obs.get();
+    assert!(!js_sys::Object::is(&appended, &replaced));
+    assert_eq!(Array::from(&replaced).to_vec(), vec![4]);
+}
+
+#[wasm_bindgen_test]
+fn list_removals_shrink_the_js_array() {
+    let obs: Observable<List<u32>> = Observable::new(vec![1, 2, 3, 4].into());
+    let mut js_obs: JsObservable = obs.reader().into();
+    let notified = Rc::new(Cell::new(0));
+    let _unsub = {
+        let notified = notified.clone();
+        js_obs.subscribe(js_fn(move |_| notified.set(notified.get() + 1)))
+    };
+
+    assert_eq!(obs.remove_at(1), Some(2));
+    assert_eq!(Array::from(&js_obs.get()).to_vec(), vec![1, 3, 4]);
+    assert_eq!(obs.pop(), Some(4));
+    assert_eq!(obs.remove_at(5), None);
+    assert_eq!(Array::from(&js_obs.get()).to_vec(), vec![1, 3]);
+    obs.clear();
+    assert_eq!(Array::from(&js_obs.get()).length(), 0);
+    assert_eq!(notified.get(), 3);
+}
+
+#[wasm_bindgen_test]
+fn map_inserts_and_removes_reach_js() {
+    let obs: Observable<Map<String, u32>> = Observable::default();
+    let js_obs: JsObservable = obs.reader().into();
+
+    assert_eq!(obs.insert("b".to_string(), 2), None);
+    assert_eq!(obs.insert("a".to_string(), 1), None);
+    assert_eq!(obs.insert("b".to_string(), 3), Some(2));
+    let map: js_sys::Map = js_obs.get().unchecked_into();
+    assert_eq!(map.size(), 2);
+    assert_eq!(map.get(&"b".into()), 3);
+    // Ordered by key
+    assert_eq!(map.keys().next().unwrap().value(), "a");
+
+    assert_eq!(obs.remove(&"a".to_string()), Some(1));
+    assert_eq!(obs.remove(&"a".to_string()), None);
+    let map: js_sys::Map = js_obs.get().unchecked_into();
+    assert_eq!(map.size(), 1);
+    assert!(!map.has(&"a".into()));
+}
+
+#[wasm_bindgen_test]
+fn list_changes_are_delivered_in_order() {
+    let obs: Observable<List<u32>> = Observable::new(vec![1].into());
+    let changes: Rc<RefCell<Vec<ListChange>>> = Rc::default();
+    let _sub = {
+        let changes = changes.clone();
+        obs.subscribe_changes(move |change| changes.borrow_mut().push(*change))
+    };
+
+    obs.push(2);
+    assert_eq!(*changes.borrow(), vec![ListChange::Push { index: 1 }]);
+
+    batch_scope(|| {
+        obs.push(3);
+        obs.remove_at(0);
+    });
+    obs.clear();
+    obs.set(vec![4].into());
+    assert_eq!(
+        *changes.borrow(),
+        vec![
+            ListChange::Push { index: 1 },
+            ListChange::Push { index: 2 },
+            ListChange::Remove { index: 0 },
+            ListChange::Clear,
+            ListChange::Reset,
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn batched_pushes_rebuild_array_once() {
+    let obs: Observable<List<u32>> = Observable::default();
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let delivered = delivered.clone();
+        js_obs.subscribe(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+
+    batch_scope(|| {
+        obs.push(1);
+        obs.push(2);
+        obs.push(3);
+    });
+
+    assert_eq!(delivered.borrow().len(), 1);
+    let array: Array = delivered.borrow()[0].clone().unchecked_into();
+    assert_eq!(array.length(), 3);
+    assert_eq!(array.get(2), 3);
+    assert_eq!(Array::from(&js_obs.get()).length(), 3);
+}
+
+#[wasm_bindgen_test]
+fn computed_getter_follows_source() {
+    let obs = Observable::new(2u32);
+    let js_obs: JsObservable = obs.reader().into();
+    js_obs.with_computed("doubled", Function::new_with_args("v", "return v * 2"));
+
+    let computed = js_obs.computed();
+    assert_eq!(Reflect::get(&computed, &"doubled".into()).unwrap(), 4);
+
+    obs.set(5);
+    assert_eq!(Reflect::get(&computed, &"doubled".into()).unwrap(), 10);
+}
+
+#[wasm_bindgen_test]
+fn manual_scheduler_flushes_debounced_update() {
+    let scheduler = Rc::new(ManualScheduler::new());
+    let previous = set_scheduler(scheduler.clone());
+
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let delivered = delivered.clone();
+        js_obs.subscribe_debounced(js_fn(move |v| delivered.borrow_mut().push(v)), 100)
+    };
+
+    obs.set(2);
+    obs.set(3);
+    assert!(delivered.borrow().is_empty());
+    assert_eq!(scheduler.pending(), 2);
+
+    scheduler.flush();
+    assert_eq!(*delivered.borrow(), vec![JsValue::from(3)]);
+
+    set_scheduler(previous);
+}
+
+#[wasm_bindgen_test]
+fn change_context_carries_previous_value() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let seen: Rc<RefCell<Vec<(JsValue, JsValue, JsValue)>>> = Rc::default();
+    let subscriber = {
+        let seen = seen.clone();
+        let closure = Closure::wrap(Box::new(move |v: JsValue, ctx: JsValue| {
+            let value = Reflect::get(&ctx, &"value".into()).unwrap();
+            let previous = Reflect::get(&ctx, &"previous".into()).unwrap();
+            seen.borrow_mut().push((v, value, previous));
+        }) as Box<dyn FnMut(JsValue, JsValue)>);
+        closure.into_js_value().unchecked_into::<Function>()
+    };
+    let _unsub = js_obs.subscribe(subscriber);
+
+    obs.set(2);
+    obs.set(3);
+    assert_eq!(
+        *seen.borrow(),
+        vec![
+            (JsValue::from(2), JsValue::from(2), JsValue::UNDEFINED),
+            (JsValue::from(3), JsValue::from(3), JsValue::from(2)),
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn subscribers_receive_change_context() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let contexts: Rc<RefCell<Vec<(JsValue, JsValue)>>> = Rc::default();
+    let subscriber = {
+        let contexts = contexts.clone();
+        let closure = Closure::wrap(Box::new(move |_: JsValue, ctx: JsValue| {
+            let generation = Reflect::get(&ctx, &"generation".into()).unwrap();
+            let kind = Reflect::get(&ctx, &"kind".into()).unwrap();
+            contexts.borrow_mut().push((generation, kind));
+        }) as Box<dyn FnMut(JsValue, JsValue)>);
+        closure.into_js_value().unchecked_into::<Function>()
+    };
+    let _unsub = js_obs.subscribe(subscriber);
+
+    obs.set(2);
+    obs.set(3);
+    assert_eq!(
+        *contexts.borrow(),
+        vec![
+            (JsValue::from(1), JsValue::from("change")),
+            (JsValue::from(2), JsValue::from("change")),
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn map_reader_exposed_to_js() {
+    let a = Observable::new(1u32);
+    let b = Observable::new(2u32);
+    let sum = map_obs!(|a: &u32, b: &u32| a + b, a, b);
+    let mut js_sum: JsObservable = sum.into();
+    assert_eq!(js_sum.get(), 3);
+
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let delivered = delivered.clone();
+        js_sum.subscribe(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+
+    a.set(10);
+    b.set(20);
+    assert_eq!(
+        *delivered.borrow(),
+        vec![JsValue::from(12), JsValue::from(30)]
+    );
+    assert_eq!(js_sum.get(), 30);
+}
+
+#[wasm_bindgen_test]
+fn lazy_map_reader_exposed_to_js() {
+    let a = Observable::new(1u32);
+    let doubled = map_obs_lazy!(|a: &u32| a * 2, a);
+    assert!(!doubled.is_computed());
+    let mut js_doubled: JsObservable = doubled.into();
+
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let delivered = delivered.clone();
+        js_doubled.subscribe(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+    a.set(10);
+    assert_eq!(*delivered.borrow(), vec![JsValue::from(20)]);
+    assert_eq!(js_doubled.get(), 20);
+}
+
+#[wasm_bindgen_test]
+fn idle_strategy_fallback_chain() {
+    let global = js_sys::global();
+    let saved: Vec<(&str, JsValue)> = ["requestIdleCallback", "requestAnimationFrame"]
+        .into_iter()
+        .map(|name| (name, Reflect::get(&global, &name.into()).unwrap()))
+        .collect();
+    let shim = |name: &str, f: &JsValue| Reflect::set(&global, &name.into(), f).unwrap();
+    let noop: JsValue = Function::new_no_args("").into();
+
+    shim("requestIdleCallback", &noop);
+    shim("requestAnimationFrame", &noop);
+    assert_eq!(IdleStrategy::detect(), IdleStrategy::IdleCallback);
+
+    shim("requestIdleCallback", &JsValue::UNDEFINED);
+    assert_eq!(IdleStrategy::detect(), IdleStrategy::AnimationFrame);
+
+    shim("requestAnimationFrame", &JsValue::UNDEFINED);
+    assert_eq!(IdleStrategy::detect(), IdleStrategy::Timeout);
+
+    for (name, f) in saved {
+        shim(name, &f);
+    }
+}
+
+#[wasm_bindgen_test]
+fn low_priority_subscriber_gets_coalesced_latest() {
+    let scheduler = Rc::new(ManualScheduler::new());
+    let previous = set_scheduler(scheduler.clone());
+
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let delivered = delivered.clone();
+        js_obs.subscribe_low_priority(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+
+    obs.set(2);
+    obs.set(3);
+    assert_eq!(scheduler.pending(), 1);
+    scheduler.flush();
+    assert_eq!(*delivered.borrow(), vec![JsValue::from(3)]);
+
+    set_scheduler(previous);
+}
+
+/// Replace document with a mock whose visibility can be toggled with set_hidden, until the guard
+/// is dropped
+fn mock_document() -> (JsValue, PatchedGlobal) {
+    let document = Function::new_no_args(
+        "const listeners = [];
+        return {
+            hidden: false,
+            addEventListener(event, cb) { if (event === 'visibilitychange') listeners.push(cb); },
+            removeEventListener(event, cb) { listeners.splice(listeners.indexOf(cb), 1); },
+            listeners,
+            setHidden(hidden) { this.hidden = hidden; listeners.slice().forEach((cb) => cb()); },
+        };",
+    )
+    .call0(&JsValue::UNDEFINED)
+    .unwrap();
+    let patched = PatchedGlobal::new("document", &document);
+    (document, patched)
+}
+
+fn set_hidden(document: &JsValue, hidden: bool) {
+    let set: Function = Reflect::get(document, &"setHidden".into())
+        .unwrap()
+        .unchecked_into();
+    set.call1(document, &hidden.into()).unwrap();
+}
+
+#[wasm_bindgen_test]
+fn subscribe_when_visible_coalesces_while_hidden() {
+    let (document, _patched) = mock_document();
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let unsub = {
+        let delivered = delivered.clone();
+        js_obs.subscribe_when_visible(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+
+    obs.set(2);
+    set_hidden(&document, true);
+    obs.set(3);
+    obs.set(4);
+    assert_eq!(*delivered.borrow(), vec![JsValue::from(2)]);
+
+    set_hidden(&document, false);
+    assert_eq!(
+        *delivered.borrow(),
+        vec![JsValue::from(2), JsValue::from(4)]
+    );
+
+    // Becoming visible with nothing buffered delivers nothing
+    set_hidden(&document, true);
+    set_hidden(&document, false);
+    assert_eq!(delivered.borrow().len(), 2);
+
+    unsub.call0(&JsValue::UNDEFINED).unwrap();
+    let listeners: Array = Reflect::get(&document, &"listeners".into())
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(listeners.length(), 0);
+}
+
+#[wasm_bindgen_test]
+fn subscribe_when_visible_without_document() {
+    assert!(!Reflect::has(&js_sys::global(), &"document".into()).unwrap());
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let unsub = {
+        let delivered = delivered.clone();
+        js_obs.subscribe_when_visible(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+    obs.set(2);
+    assert_eq!(*delivered.borrow(), vec![JsValue::from(2)]);
+    unsub.call0(&JsValue::UNDEFINED).unwrap();
+}
+
+#[cfg(feature = "chrono")]
+#[wasm_bindgen_test]
+fn datetime_round_trips_through_js_date() {
+    use chrono::{DateTime, FixedOffset};
+    use observable_react::date::JsDateTime;
+
+    let zoned = DateTime::parse_from_rfc3339("2024-03-01T12:34:56.789+02:00").unwrap();
+    let obs = Observable::new(JsDateTime::from_zoned(&zoned));
+    let js_obs: JsObservable = obs.reader().into();
+
+    let date: js_sys::Date = js_obs.get().unchecked_into();
+    assert_eq!(date.get_time(), zoned.timestamp_millis() as f64);
+    assert_eq!(date.get_utc_hours(), 10);
+
+    let back = JsDateTime::try_from(JsValue::from(date)).unwrap();
+    assert_eq!(back.0, zoned.with_timezone(&chrono::Utc));
+    assert_eq!(
+        back.0.with_timezone(&FixedOffset::east_opt(7200).unwrap()),
+        zoned
+    );
+
+    assert!(JsDateTime::try_from(JsValue::from("2024-03-01")).is_err());
+}
+
+#[wasm_bindgen_test]
+fn subscribe_mapped_transforms_per_subscriber() {
+    let obs = Observable::new(1.5f64);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let raw: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let formatted: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _raw = {
+        let raw = raw.clone();
+        js_obs.subscribe_mapped(
+            Function::new_with_args("v", "return v"),
+            js_fn(move |v| raw.borrow_mut().push(v)),
+        )
+    };
+    let _formatted = {
+        let formatted = formatted.clone();
+        js_obs.subscribe_mapped(
+            Function::new_with_args("v", "return `$${v.toFixed(2)}`"),
+            js_fn(move |v| formatted.borrow_mut().push(v)),
+        )
+    };
+
+    obs.set(2.25);
+    assert_eq!(*raw.borrow(), vec![JsValue::from(2.25)]);
+    assert_eq!(*formatted.borrow(), vec![JsValue::from("$2.25")]);
+}
+
+#[wasm_bindgen_test]
+fn subscribe_frozen_delivers_deeply_frozen_value() {
+    // The parent is already frozen, but only shallowly, and the value refers back to itself
+    let make = Function::new_no_args(
+        "const v = { nested: { count: 1 }, list: [1, 2] }; v.self = v; return Object.freeze(v)",
+    );
+    let obs = Observable::new(make.call0(&JsValue::UNDEFINED).unwrap());
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let delivered = delivered.clone();
+        js_obs.subscribe_frozen(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+    obs.set(make.call0(&JsValue::UNDEFINED).unwrap());
+
+    let value = delivered.borrow()[0].clone();
+    let nested = Reflect::get(&value, &"nested".into()).unwrap();
+    let list = Reflect::get(&value, &"list".into()).unwrap();
+    for v in [&value, &nested, &list] {
+        assert!(js_sys::Object::is_frozen(
+            v.unchecked_ref::<js_sys::Object>()
+        ));
+    }
+
+    let mutate = Function::new_with_args("o", "'use strict'; o.nested.count = 2;");
+    assert!(mutate.call1(&JsValue::UNDEFINED, &value).is_err());
+    let push = Function::new_with_args("o", "'use strict'; o.list.push(3);");
+    assert!(push.call1(&JsValue::UNDEFINED, &value).is_err());
+
+    // Only the subscriber's copy is frozen
+    let source = obs.value().clone();
+    assert!(!js_sys::Object::is(&value, &source));
+    let source_nested = Reflect::get(&source, &"nested".into()).unwrap();
+    assert!(!js_sys::Object::is_frozen(
+        source_nested.unchecked_ref::<js_sys::Object>()
+    ));
+}
+
+#[wasm_bindgen_test]
+fn subscribe_frozen_leaves_exported_structs_alone() {
+    let inner = Observable::new(1u32);
+    let obs = Observable::new(JsValue::from(JsObservable::from(inner.reader())));
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let delivered: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let delivered = delivered.clone();
+        js_obs.subscribe_frozen(js_fn(move |v| delivered.borrow_mut().push(v)))
+    };
+    obs.set(JsObservable::from(inner.reader()).into());
+
+    let value = delivered.borrow()[0].clone();
+    assert!(!js_sys::Object::is_frozen(
+        value.unchecked_ref::<js_sys::Object>()
+    ));
+    Function::new_with_args("o", "'use strict'; o.free()")
+        .call1(&JsValue::UNDEFINED, &value)
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+fn get_clone_is_independent_of_source() {
+    let value = Function::new_no_args("return { nested: { count: 1 } }")
+        .call0(&JsValue::UNDEFINED)
+        .unwrap();
+    let obs = Observable::new(value);
+    let js_obs: JsObservable = obs.reader().into();
+
+    let copy = js_obs.get_clone().unwrap();
+    Function::new_with_args("o", "o.nested.count = 2")
+        .call1(&JsValue::UNDEFINED, &copy)
+        .unwrap();
+
+    let count = |v: &JsValue| {
+        let nested = Reflect::get(v, &"nested".into()).unwrap();
+        Reflect::get(&nested, &"count".into()).unwrap()
+    };
+    assert_eq!(count(&copy), 2);
+    assert_eq!(count(&js_obs.get()), 1);
+    assert_eq!(count(&obs.value()), 1);
+}
+
+#[wasm_bindgen_test]
+fn proxy_reads_and_writes_through() {
+    let initial = Function::new_no_args("return { count: 1, nested: { label: 'a' } }")
+        .call0(&JsValue::UNDEFINED)
+        .unwrap();
+    let obs = Observable::new(initial);
+    let js_obs: JsObservable = obs.reader().into();
+
+    // Writes replace the value with an updated copy
+    let setter = {
+        let weak = obs.downgrade();
+        let reader = obs.reader();
+        Closure::wrap(Box::new(move |path: Array, value: JsValue| {
+            let copy = Function::new_with_args("v", "return structuredClone(v)")
+                .call1(&JsValue::UNDEFINED, &reader.value())
+                .unwrap();
+            let last = path.pop();
+            let parent = path
+                .iter()
+                .fold(copy.clone(), |v, key| Reflect::get(&v, &key).unwrap());
+            Reflect::set(&parent, &last, &value).unwrap();
+            weak.set(copy);
+        }) as Box<dyn Fn(Array, JsValue)>)
+    };
+    let proxy: JsValue = js_obs
+        .as_proxy(setter.as_ref().unchecked_ref::<Function>().clone())
+        .into();
+    let read = |path: &str| {
+        Function::new_with_args("p", &format!("return p.{}", path))
+            .call1(&JsValue::UNDEFINED, &proxy)
+            .unwrap()
+    };
+
+    assert_eq!(read("count"), 1);
+    assert_eq!(read("nested.label"), "a");
+
+    // Rust sets show up in the proxy
+    obs.set(
+        Function::new_no_args("return { count: 2, nested: { label: 'b' } }")
+            .call0(&JsValue::UNDEFINED)
+            .unwrap(),
+    );
+    assert_eq!(read("count"), 2);
+    assert_eq!(read("nested.label"), "b");
+
+    // Writes through the proxy, including nested ones, reach the observable
+    Function::new_with_args("p", "'use strict'; p.count = 3; p.nested.label = 'c'")
+        .call1(&JsValue::UNDEFINED, &proxy)
+        .unwrap();
+    assert_eq!(Reflect::get(&obs.value(), &"count".into()).unwrap(), 3);
+    assert_eq!(read("nested.label"), "c");
+    assert!(
+        Function::new_with_args("p", "return 'count' in p && !('missing' in p)")
+            .call1(&JsValue::UNDEFINED, &proxy)
+            .unwrap()
+            .is_truthy()
+    );
+}
+
+#[wasm_bindgen_test]
+fn proxy_enumerates_like_the_value() {
+    let value =
+        Function::new_no_args("return { count: 1, nested: { label: 'a' }, tags: ['x', 'y'] }")
+            .call0(&JsValue::UNDEFINED)
+            .unwrap();
+    let obs = Observable::new(value);
+    let js_obs: JsObservable = obs.reader().into();
+    let proxy: JsValue = js_obs.as_proxy(Function::new_no_args("")).into();
+    let eval = |body: &str| {
+        Function::new_with_args("p", body)
+            .call1(&JsValue::UNDEFINED, &proxy)
+            .unwrap()
+    };
+
+    let json = r#"{"count":1,"nested":{"label":"a"},"tags":["x","y"]}"#;
+    assert_eq!(eval("return JSON.stringify(p)"), json);
+    assert_eq!(eval("return JSON.stringify({ ...p })"), json);
+    assert_eq!(eval("return Object.keys(p).join()"), "count,nested,tags");
+    assert_eq!(
+        eval("const keys = []; for (const k in p.nested) keys.push(k); return keys.join()"),
+        "label"
+    );
+    assert_eq!(
+        eval("return Array.isArray(p.tags) && [...p.tags].join()"),
+        "x,y"
+    );
+
+    obs.set(
+        Function::new_no_args("return { count: 2 }")
+            .call0(&JsValue::UNDEFINED)
+            .unwrap(),
+    );
+    assert_eq!(eval("return JSON.stringify({ ...p })"), r#"{"count":2}"#);
+}
+
+#[wasm_bindgen_test]
+fn combine_js_follows_all_sources() {
+    let a = Observable::new(1);
+    let b = Observable::new(10);
+    let sources: Array = [a.reader(), b.reader()]
+        .into_iter()
+        .map(|r| JsValue::from(JsObservable::from(r)))
+        .collect();
+
+    let combined =
+        observable_react::combine_js(sources, Function::new_with_args("a, b", "return a + b"));
+    assert_eq!(combined.get(), 11);
+
+    a.set(2);
+    assert_eq!(combined.get(), 12);
+    b.set(20);
+    assert_eq!(combined.get(), 22);
+}
+
+#[wasm_bindgen_test]
+fn subscribe_delivers_values_until_unsubscribed() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let seen: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let unsub = js_obs.subscribe({
+        let seen = seen.clone();
+        js_fn(move |v| seen.borrow_mut().push(v))
+    });
+
+    obs.set(2);
+    obs.set(3);
+    assert_eq!(*seen.borrow(), vec![JsValue::from(2), JsValue::from(3)]);
+
+    // Unsubscribe the way JS does, by calling the returned function
+    Function::new_with_args("unsub", "unsub()")
+        .call1(&JsValue::UNDEFINED, &unsub)
+        .unwrap();
+    obs.set(4);
+    assert_eq!(seen.borrow().len(), 2);
+    assert_eq!(js_obs.get(), 4);
+}
+
+#[wasm_bindgen_test]
+fn map_calls_back_with_current_value() {
+    let obs = Observable::new(String::from("a"));
+    let js_obs: JsObservable = obs.reader().into();
+    let double = Function::new_with_args("v", "return v + v");
+
+    let mapped: Array = js_obs.map(double.clone()).unwrap().unchecked_into();
+    assert_eq!(mapped.to_vec(), vec![JsValue::from("aa")]);
+
+    obs.set("b".into());
+    let mapped: Array = js_obs.map(double).unwrap().unchecked_into();
+    assert_eq!(mapped.to_vec(), vec![JsValue::from("bb")]);
+}
+
+#[wasm_bindgen_test]
+fn values_iterates_a_snapshot_of_the_list() {
+    let obs: Observable<List<u32>> = Observable::new(vec![1, 2].into());
+    let js_obs: JsObservable = obs.reader().into();
+
+    let values = js_obs.values();
+    obs.push(3);
+    let items: Vec<JsValue> = values.into_iter().map(Result::unwrap).collect();
+    assert_eq!(items, vec![1, 2]);
+
+    let items: Vec<JsValue> = js_obs.values().into_iter().map(Result::unwrap).collect();
+    assert_eq!(items, vec![1, 2, 3]);
+}
+
+#[wasm_bindgen_test]
+fn throwing_map_callback_is_rethrown() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+    let calls: Rc<Cell<usize>> = Rc::default();
+    let _unsub = js_obs.subscribe({
+        let calls = calls.clone();
+        js_fn(move |_| calls.set(calls.get() + 1))
+    });
+
+    let err = js_obs
+        .map(Function::new_with_args("v", "throw new Error('boom')"))
+        .unwrap_err();
+    assert!(err.is_instance_of::<js_sys::Error>());
+
+    // Neither the observable nor its subscribers are affected
+    obs.set(2);
+    assert_eq!(calls.get(), 1);
+    assert_eq!(js_obs.get(), 2);
+}
+
+#[wasm_bindgen_test]
+fn subscribe_distinct_by_uses_custom_equality() {
+    let meta = Function::new_no_args("return { label: 'a' }")
+        .call0(&JsValue::UNDEFINED)
+        .unwrap();
+    let make = Function::new_with_args("n, meta", "return { n, meta }");
+    let value = |n: u32| make.call2(&JsValue::UNDEFINED, &n.into(), &meta).unwrap();
+    let obs = Observable::new(value(1));
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let shallow_equal = Function::new_with_args(
+        "a, b",
+        "const ka = Object.keys(a), kb = Object.keys(b);
+         return ka.length === kb.length && ka.every(k => a[k] === b[k]);",
+    );
+    let seen: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let seen = seen.clone();
+        js_obs.subscribe_distinct_by(shallow_equal, js_fn(move |v| seen.borrow_mut().push(v)))
+    };
+
+    // A new object whose only change is deeper down is not delivered
+    Reflect::set(&meta, &"label".into(), &"b".into()).unwrap();
+    obs.set(value(1));
+    assert!(seen.borrow().is_empty());
+
+    obs.set(value(2));
+    obs.set(value(2));
+    assert_eq!(seen.borrow().len(), 1);
+    assert_eq!(Reflect::get(&seen.borrow()[0], &"n".into()).unwrap(), 2);
+}
+
+#[wasm_bindgen_test]
+fn subscribe_path_ignores_sibling_changes() {
+    let tree = Function::new_with_args(
+        "value, sibling",
+        "return { child: [{ value, other: sibling }], name: 'root' }",
+    );
+    let make = |value: u32, sibling: u32| {
+        tree.call2(&JsValue::UNDEFINED, &value.into(), &sibling.into())
+            .unwrap()
+    };
+    let obs = Observable::new(make(1, 1));
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let seen: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let path: Array = [JsValue::from("child"), 0.into(), "value".into()]
+        .iter()
+        .collect();
+    let _unsub = {
+        let seen = seen.clone();
+        js_obs.subscribe_path(path, js_fn(move |v| seen.borrow_mut().push(v)))
+    };
+
+    obs.set(make(1, 2));
+    assert!(seen.borrow().is_empty());
+
+    obs.set(make(3, 2));
+    assert_eq!(*seen.borrow(), vec![JsValue::from(3)]);
+}
+
+#[wasm_bindgen_test]
+fn custom_serializer_round_trips() {
+    use observable_react::serializer::Serializer;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+    /// Points as [x, y] arrays
+    struct PairSerializer;
+    impl Serializer<Point> for PairSerializer {
+        fn serialize(&self, p: &Point) -> JsValue {
+            Array::of2(&p.x.into(), &p.y.into()).into()
+        }
+    }
+    let deserialize = |v: &JsValue| {
+        let pair: &Array = v.unchecked_ref();
+        Point {
+            x: pair.get(0).as_f64().unwrap(),
+            y: pair.get(1).as_f64().unwrap(),
+        }
+    };
+
+    let obs = Observable::new(Point { x: 1.0, y: 2.0 });
+    let mut js_obs = JsObservable::with_serializer(obs.reader(), PairSerializer);
+    assert_eq!(deserialize(&js_obs.get()), Point { x: 1.0, y: 2.0 });
+
+    let seen: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let seen = seen.clone();
+        js_obs.subscribe(js_fn(move |v| seen.borrow_mut().push(v)))
+    };
+    obs.set(Point { x: 3.0, y: 4.0 });
+    assert_eq!(deserialize(&seen.borrow()[0]), *obs.value());
+}
+
+#[wasm_bindgen_test]
+fn subscribe_immediate_delivers_current_value() {
+    let obs = Observable::new(1u32);
+    let mut js_obs: JsObservable = obs.reader().into();
+
+    let seen: Rc<RefCell<Vec<(JsValue, JsValue)>>> = Rc::default();
+    let subscriber = {
+        let seen = seen.clone();
+        let closure = Closure::wrap(Box::new(move |v: JsValue, ctx: JsValue| {
+            let kind = Reflect::get(&ctx, &"kind".into()).unwrap();
+            seen.borrow_mut().push((v, kind));
+        }) as Box<dyn FnMut(JsValue, JsValue)>);
+        closure.into_js_value().unchecked_into::<Function>()
+    };
+    let _unsub = js_obs.subscribe_immediate(subscriber).unwrap();
+    assert_eq!(*seen.borrow(), vec![(1.into(), "initial".into())]);
+
+    obs.set(2);
+    assert_eq!(seen.borrow()[1], (2.into(), "change".into()));
+
+    // A subscriber which throws on the current value is not subscribed
+    let failed =
+        js_obs.subscribe_immediate(Function::new_with_args("v", "throw new Error('boom')"));
+    assert!(failed.is_err());
+    assert_eq!(obs.set_and_count(3), 1);
+}
+
+#[wasm_bindgen_test]
+fn component_binder_coalesces_force_updates() {
+    let scheduler = Rc::new(ManualScheduler::new());
+    let previous = set_scheduler(scheduler.clone());
+
+    let component =
+        Function::new_no_args("return { updates: 0, forceUpdate() { this.updates++ } }")
+            .call0(&JsValue::UNDEFINED)
+            .unwrap();
+    let updates = || Reflect::get(&component, &"updates".into()).unwrap();
+    let binder = ReactComponentBinder::new(component.clone().unchecked_into());
+
+    let observables: Vec<Observable<u32>> = (0..3).map(Observable::new).collect();
+    for obs in &observables {
+        binder.bind(&obs.reader().into());
+    }
+
+    for obs in &observables {
+        obs.set(10);
+    }
+    assert_eq!(updates(), 0);
+    assert_eq!(scheduler.pending(), 1);
+    scheduler.flush();
+    assert_eq!(updates(), 1);
+
+    binder.unbind_all();
+    observables[0].set(20);
+    scheduler.flush();
+    assert_eq!(updates(), 1);
+
+    set_scheduler(previous);
+}
+
+#[wasm_bindgen_test]
+fn animated_tweens_between_values() {
+    let scheduler = Rc::new(ManualScheduler::new());
+    let previous = set_scheduler(scheduler.clone());
+
+    let obs = Observable::new(0.0);
+    let js_obs: JsObservable = obs.reader().into();
+    let mut animated = js_obs.animated(100.0);
+    let seen: Rc<RefCell<Vec<JsValue>>> = Rc::default();
+    let _unsub = {
+        let seen = seen.clone();
+        animated.subscribe(js_fn(move |v| seen.borrow_mut().push(v)))
+    };
+    assert_eq!(animated.get(), 0.0);
+
+    obs.set(10.0);
+    for _ in 0..4 {
+        scheduler.advance(25.0);
+        scheduler.run_pending();
+    }
+    assert_eq!(
+        *seen.borrow(),
+        vec![2.5, 5.0, 7.5, 10.0]
+            .into_iter()
+            .map(JsValue::from)
+            .collect::<Vec<_>>()
+    );
+    // Done, so no more frames are requested
+    assert_eq!(scheduler.pending(), 0);
+
+    // A custom easing which jumps straight to the end of the tween
+    let eased = js_obs.animated_with(100.0, Function::new_with_args("t", "return 1"));
+    obs.set(20.0);
+    scheduler.advance(10.0);
+    scheduler.run_pending();
+    assert_eq!(eased.get(), 20.0);
+    assert_eq!(animated.get(), 11.0);
+
+    set_scheduler(previous);
+}